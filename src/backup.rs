@@ -0,0 +1,274 @@
+use std::{fs, path, sync::Mutex, time};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{self, utils::relative_to};
+
+/// Directory for data of the tool, relative to the directory of the configuration file.
+const DATA_DIR: &str = ".run-clang-format";
+/// Directory for backups, relative to [`DATA_DIR`].
+const BACKUP_DIR: &str = "backups";
+const MANIFEST_NAME: &str = "manifest.json";
+/// Number of runs for which backups are kept, older backups are deleted.
+const KEEP_RUNS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    /// Canonical path of the formatted file.
+    path: path::PathBuf,
+    /// Name of the file holding the original content, within the run's backup directory.
+    backup: String,
+    /// Hash of the formatted content, used to detect changes after formatting.
+    formatted_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    run_id: String,
+    /// Seconds since the unix epoch.
+    created: u64,
+    files: Vec<Entry>,
+}
+
+/// Stable FNV-1a hash, such that hashes in manifests remain valid across releases.
+fn hash(content: &[u8]) -> String {
+    let hash = content.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn backups_dir(root: &path::Path) -> path::PathBuf {
+    root.join(DATA_DIR).join(BACKUP_DIR)
+}
+
+/// Backup of the original content of all files that are changed by a single run. Nothing is
+/// written until the first file changes, such that runs without changes leave no traces.
+#[derive(Debug)]
+pub struct Backup {
+    run_id: String,
+    created: u64,
+    data_dir: path::PathBuf,
+    dir: path::PathBuf,
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl Backup {
+    /// Prepares the backup for a new run within the directory `root` of the configuration file.
+    pub fn new(root: &path::Path) -> Backup {
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap_or_default();
+        // run identifiers sort in the order the runs have been executed
+        let run_id = format!(
+            "{}-{:03}-{}",
+            created.as_secs(),
+            created.subsec_millis(),
+            std::process::id()
+        );
+
+        Backup {
+            dir: backups_dir(root).join(&run_id),
+            data_dir: root.join(DATA_DIR),
+            run_id,
+            created: created.as_secs(),
+            entries: Mutex::new(vec![]),
+        }
+    }
+
+    /// Saves the `original` content of the file at `path`, which has been changed to
+    /// `formatted`. The backup directory is created for the first changed file.
+    pub fn save(&self, path: &path::Path, original: &[u8], formatted: &[u8]) -> eyre::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.is_empty() {
+            fs::create_dir_all(&self.dir)
+                .wrap_err(format!(
+                    "Failed to create backup directory {}",
+                    self.dir.to_string_lossy()
+                ))
+                .suggestion(
+                    "Please check the permissions for the directory of the configuration file, \
+                     or use --no-backup to disable backups",
+                )?;
+            // the data directory should never be committed
+            let _ = fs::write(self.data_dir.join(".gitignore"), "*\n");
+        }
+
+        let backup = format!("{}.orig", entries.len());
+        fs::write(self.dir.join(&backup), original).wrap_err(format!(
+            "Failed to create a backup of {}",
+            path.to_string_lossy()
+        ))?;
+        entries.push(Entry {
+            path: path.to_path_buf(),
+            backup,
+            formatted_hash: hash(formatted),
+        });
+        Ok(())
+    }
+
+    /// Writes the manifest of the run and removes backups of older runs. Provides the run
+    /// identifier and the number of changed files, or `None` if no file has been changed.
+    pub fn finish(self) -> eyre::Result<Option<(String, usize)>> {
+        let mut files = self.entries.into_inner().unwrap();
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let count = files.len();
+        let manifest = Manifest {
+            run_id: self.run_id.clone(),
+            created: self.created,
+            files,
+        };
+        fs::write(
+            self.dir.join(MANIFEST_NAME),
+            serde_json::to_string_pretty(&manifest)?,
+        )
+        .wrap_err("Failed to write the backup manifest")?;
+
+        if let Some(parent) = self.dir.parent() {
+            let runs = list_runs(parent);
+            for run in runs.iter().take(runs.len().saturating_sub(KEEP_RUNS)) {
+                let _ = fs::remove_dir_all(parent.join(&run.run_id));
+            }
+        }
+        Ok(Some((self.run_id, count)))
+    }
+}
+
+/// Removes the data directory of the tool in `root` if no backups are left.
+fn remove_if_empty(root: &path::Path) {
+    if fs::remove_dir(backups_dir(root)).is_ok() {
+        let data_dir = root.join(DATA_DIR);
+        let _ = fs::remove_file(data_dir.join(".gitignore"));
+        let _ = fs::remove_dir(data_dir);
+    }
+}
+
+/// All runs with a valid manifest in `dir`, oldest first.
+fn list_runs(dir: &path::Path) -> Vec<Manifest> {
+    let mut runs: Vec<_> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join(MANIFEST_NAME)).ok())
+        .filter_map(|content| serde_json::from_str::<Manifest>(&content).ok())
+        .collect();
+    runs.sort_by(|a, b| (a.created, &a.run_id).cmp(&(b.created, &b.run_id)));
+    runs
+}
+
+/// Restores all files changed by the run `run_id`, or by the latest run, and removes its backup.
+/// Files that have been modified after formatting are skipped unless `force` is set.
+pub fn restore(
+    data: &cli::Data,
+    run_id: &Option<String>,
+    list: bool,
+    force: bool,
+) -> eyre::Result<()> {
+    let root = &data.json.root;
+    let dir = backups_dir(root);
+    let runs = list_runs(&dir);
+
+    if list {
+        for run in runs.iter().rev() {
+            println!("{} ({} files)", run.run_id, run.files.len());
+        }
+        return Ok(());
+    }
+
+    let run = match run_id {
+        None => runs.last(),
+        Some(run_id) => runs.iter().find(|run| run.run_id == *run_id),
+    };
+    let run = match run {
+        Some(run) => run,
+        None => {
+            return Err(match run_id {
+                None => eyre!("No backups found for '{}'", data.json.name),
+                Some(run_id) => eyre!("No backup found for run '{run_id}'"),
+            })
+            .suggestion("Please use 'restore --list' to list the available backups")
+        }
+    };
+
+    let rel = |path: &path::Path| relative_to(root, path).to_string_lossy().to_string();
+    let mut skipped = vec![];
+    for entry in run.files.iter() {
+        if !force {
+            let current = fs::read(&entry.path).map(|content| hash(&content));
+            if current.ok().as_ref() != Some(&entry.formatted_hash) {
+                skipped.push(rel(&entry.path));
+                continue;
+            }
+        }
+        let content = fs::read(dir.join(&run.run_id).join(&entry.backup))
+            .wrap_err(format!("Failed to read the backup of {}", rel(&entry.path)))?;
+        fs::write(&entry.path, content)
+            .wrap_err(format!("Failed to restore {}", rel(&entry.path)))?;
+        log::info!("Restored {}", rel(&entry.path));
+    }
+
+    if !skipped.is_empty() {
+        return Err(eyre!(
+            "Skipped the following files, since they have been modified after formatting:\n{}\n ",
+            skipped.join("\n")
+        ))
+        .suggestion("Please use 'restore --force' to overwrite the modified files");
+    }
+
+    fs::remove_dir_all(dir.join(&run.run_id)).wrap_err("Failed to remove the restored backup")?;
+    remove_if_empty(root);
+    log::info!(
+        "Restored {} files of run {}",
+        run.files.len(),
+        console::style(&run.run_id).bold()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup() {
+        let tmp = tempfile::Builder::new().prefix("backup").tempdir().unwrap();
+        let root = tmp.path();
+
+        // runs without changes leave no traces
+        let backup = Backup::new(root);
+        assert_eq!(None, backup.finish().unwrap());
+        assert!(!root.join(DATA_DIR).exists());
+
+        let backup = Backup::new(root);
+        backup.save(&root.join("a.c"), b"a  ", b"a").unwrap();
+        let (run_id, count) = backup.finish().unwrap().unwrap();
+        assert_eq!(1, count);
+
+        let runs = list_runs(&backups_dir(root));
+        assert_eq!(1, runs.len());
+        assert_eq!(run_id, runs[0].run_id);
+        assert_eq!(hash(b"a"), runs[0].files[0].formatted_hash);
+        assert_eq!(
+            b"a  ".to_vec(),
+            fs::read(
+                backups_dir(root)
+                    .join(&run_id)
+                    .join(&runs[0].files[0].backup)
+            )
+            .unwrap()
+        );
+
+        // the data directory is removed with the last backup
+        fs::remove_dir_all(backups_dir(root).join(&run_id)).unwrap();
+        remove_if_empty(root);
+        assert!(!root.join(DATA_DIR).exists());
+    }
+}
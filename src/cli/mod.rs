@@ -1,4 +1,4 @@
-use std::{path, process};
+use std::{collections::BTreeMap, path, process, time};
 
 mod handlers;
 mod logging;
@@ -10,32 +10,93 @@ use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
 use schemars::{schema_for, JsonSchema};
 use serde::Deserialize;
 
+/// Policy for following symlinks while searching for files.
+#[derive(Deserialize, Debug, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FollowSymlinks {
+    /// Symlinks are skipped.
+    Never,
+    /// Symlinks to files are accepted, but symlinks to directories are not walked.
+    #[default]
+    Files,
+    /// All symlinks are followed, symlink cycles are skipped with a warning.
+    All,
+}
+
+/// Policy for broken symlinks matching any of the paths.
+#[derive(Deserialize, Debug, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BrokenSymlinks {
+    /// Broken symlinks are silently skipped.
+    #[default]
+    Skip,
+    /// Broken symlinks are skipped with a warning.
+    Warn,
+    /// Broken symlinks lead to an error.
+    Error,
+}
+
 #[derive(Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")] // removed: deny_unknown_fields
 pub struct JsonModel {
     /// List of paths and/or globs.
     /// This list may contain paths or shell-style globs to define the files that should be
-    /// filtered. Paths or globs that resolve to folders will be silently ignored. Any path
-    /// contained in this list must be specified relative to the configuration file.
+    /// filtered. Paths or globs that resolve to folders will be silently ignored unless the
+    /// field `extensions` is specified. Any path contained in this list must be specified
+    /// relative to the configuration file. Entries starting with `!` exclude all matching paths
+    /// and directories; entries are evaluated in order, i.e., a later entry can re-include paths
+    /// that have been excluded by a preceding entry.
     pub paths: Vec<String>,
     /// Optional list of globs used for efficiently pre-filtering paths.
     /// In contrast to the post-filter, searching will completely skip all paths and its siblings
     /// for any match with any pattern. E.g., [".git"] will skip all ".git" folders completely.
     /// By default, i.e., if this field is not present in the configuration, the tool will skip all
     /// hidden paths and files. Set this entry to an empty list to prevent any kind of
-    /// pre-filtering.
+    /// pre-filtering. Hidden paths can also be configured explicitly using `includeHidden`.
     pub filter_pre: Option<Vec<String>>,
     /// Optional list of globs to use for post-filtering.
     /// This filter will be applied for all paths _after_ they have been resolved. In contrast to
     /// the pre-filter, siblings of paths will not be filtered without the corresponding glob. E.g.,
     /// ".git" will not filter any files, only ".git/**" would. Notice that only
     pub filter_post: Option<Vec<String>>,
+    /// Optional allow-list of file extensions, e.g., ["c", "h", "cpp", "hpp"].
+    /// Only files with any of the given extensions are formatted. If this field is present,
+    /// paths or globs in `paths` that resolve to folders are expanded into all files below them.
+    pub extensions: Option<Vec<String>>,
+    /// Optional flag to skip all paths that are ignored by `.gitignore` files, the
+    /// `.git/info/exclude` file, or `.clang-format-ignore` files. Defaults to `false`.
+    /// Notice that `.gitignore` files are only considered within `git` repositories.
+    pub respect_ignore_files: Option<bool>,
+    /// Optional flag to include or skip hidden paths and files, independent of `filterPre`.
+    /// If this field is not present, hidden paths are skipped unless `filterPre` is specified.
+    pub include_hidden: Option<bool>,
+    /// Optional policy for following symlinks: "never", "files" (default) or "all".
+    pub follow_symlinks: Option<FollowSymlinks>,
+    /// Optional policy for broken symlinks: "skip" (default), "warn" or "error".
+    pub broken_symlinks: Option<BrokenSymlinks>,
     /// Optional path to a `.clang-format` style file (can be specified via --style)
     pub style_file: Option<path::PathBuf>,
     /// Optional path where the `.clang-format` file should be copied to while executing
     pub style_root: Option<path::PathBuf>,
     /// Optional path to the `clang-format` executable or command name
     pub command: Option<path::PathBuf>,
+    /// Optional command prefix used for launching `clang-format` (can be specified via
+    /// --launcher). The first element is the launcher executable, e.g., a wrapper script or
+    /// `["nix", "shell", "nixpkgs#clang-tools", "-c"]`. The `command` is appended to this list.
+    pub command_launcher: Option<Vec<String>>,
+    /// Optional list of additional arguments passed to `clang-format` (can be specified after
+    /// `--` on the command line). The arguments replace the default options `-fallback-style`,
+    /// `-style` and `--Werror`, e.g., ["--fallback-style=LLVM", "--sort-includes=false"].
+    /// Values must be passed as `--option=value`. Options that are controlled by this tool,
+    /// e.g., `-i` or `--dry-run`, and options restricting the formatted range are rejected.
+    pub extra_args: Option<Vec<String>>,
+    /// Optional map of globs to languages for files with non-standard extensions, e.g.,
+    /// { "*.ipp": "cpp", "*.proto.in": "proto" }. Matching files are passed to `clang-format` via
+    /// stdin using the `--assume-filename` parameter. Globs without a path separator match the
+    /// file name, all other globs match the path relative to the configuration file, e.g.,
+    /// "src/gen/*.inc". Supported languages are c, cpp, cuda, objc, objcpp, csharp, java,
+    /// javascript, typescript, json, proto, textproto, tablegen and verilog.
+    pub language_map: Option<BTreeMap<String, String>>,
 
     #[serde(skip)]
     /// Parent directory of the Json file, used to resolve paths specified within
@@ -45,10 +106,44 @@ pub struct JsonModel {
     pub name: String,
 }
 
+/// Options for the `list` subcommand.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListOptions {
+    /// Print the file set as JSON instead of one path per line.
+    pub json: bool,
+    /// Also print the paths that have been removed by filters or negated patterns.
+    pub show_filtered: bool,
+}
+
 #[derive(Debug)]
 pub enum Command {
     Format,
-    Check,
+    /// Check the format, and format the mismatching files if `fix` is set.
+    Check {
+        fix: bool,
+    },
+    /// Print the resolved file set without executing clang-format.
+    List(ListOptions),
+    /// Explain why the given path is or is not selected.
+    Explain(path::PathBuf),
+    /// Report problems of the configuration, failing for warnings in strict mode.
+    Validate {
+        strict: bool,
+    },
+    /// Print diagnostics about the clang-format executable and the style files.
+    Doctor,
+    /// Restore the files changed by the given or the latest run, or list the available backups.
+    Restore {
+        run_id: Option<String>,
+        list: bool,
+        force: bool,
+    },
+    /// Watch the selected files, the configuration and the style file, and format or check the
+    /// changed files.
+    Watch {
+        check: bool,
+        interval: time::Duration,
+    },
 }
 
 #[derive(Debug)]
@@ -59,6 +154,10 @@ pub struct Data {
     pub style: Option<path::PathBuf>,
     /// Command-line override for the clang-format executable
     pub command: Option<path::PathBuf>,
+    /// Command-line override for the launcher prefix of the clang-format executable
+    pub launcher: Option<Vec<String>>,
+    /// Command-line override for additional clang-format arguments (passed after `--`)
+    pub args: Option<Vec<String>>,
     /// Command-line parameter for the number of jobs to use for executing clang-format
     /// If `None` then all available jobs should be used, else the specified number of jobs.
     pub jobs: Option<u8>,
@@ -66,6 +165,54 @@ pub struct Data {
     pub cmd: Command,
     /// Check that all files are within the .clang-format root directory.
     pub strict_root: bool,
+    /// Fail if style files within the style root shadow the placed style file.
+    pub strict_style: bool,
+    /// Warn about files that are matched by more than one entry in `paths`.
+    pub warn_overlaps: bool,
+    /// Print the files that have been changed by formatting.
+    pub list_changed: bool,
+    /// Write the changes to a patch file instead of formatting the files in place.
+    pub emit_patch: Option<path::PathBuf>,
+    /// Write the formatted files into a mirrored tree instead of formatting them in place.
+    pub output_dir: Option<path::PathBuf>,
+    /// Keep a backup of the files changed by formatting, disabled by `--no-backup`.
+    pub backup: bool,
+    /// Format all files twice in memory and report files where the second pass changes them.
+    pub verify_idempotent: bool,
+    /// Format via stdin and write files using an atomic rename instead of `clang-format -i`.
+    pub atomic_write: bool,
+    /// Format stdin to stdout, assuming it is the content of the given file.
+    pub stdin_filename: Option<path::PathBuf>,
+}
+
+impl Data {
+    /// Loads the configuration file `json` without any command-line overrides, e.g., for using
+    /// the configuration as a library.
+    pub fn load<P>(json: P) -> eyre::Result<Data>
+    where
+        P: AsRef<path::Path>,
+    {
+        let json = JsonModel::load(json).wrap_err("Invalid configuration file")?;
+        Ok(Data {
+            json,
+            style: None,
+            command: None,
+            launcher: None,
+            args: None,
+            jobs: None,
+            cmd: Command::Format,
+            strict_root: false,
+            strict_style: false,
+            warn_overlaps: false,
+            list_changed: false,
+            emit_patch: None,
+            output_dir: None,
+            backup: true,
+            verify_idempotent: false,
+            atomic_write: false,
+            stdin_filename: None,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -74,40 +221,57 @@ pub struct Builder {
 }
 
 impl Builder {
+    fn arg_json() -> clap::Arg {
+        arg!(<JSON>)
+            .help("Path/configuration as .json")
+            .value_parser(clap::value_parser!(std::path::PathBuf))
+    }
+
+    fn arg_style() -> clap::Arg {
+        arg!(-s --style ... "Optional path to .clang-format style file. \
+                             Overrides <JSON> configuration")
+        .value_parser(clap::value_parser!(std::path::PathBuf))
+        .required(false)
+        .action(clap::ArgAction::Set)
+    }
+
+    fn arg_command() -> clap::Arg {
+        arg!(-c --command ... "Optional path to executable or clang-format command. \
+                               Overrides <JSON> configuration, defaults to `clang-format`")
+        // .default_value("clang-format")
+        .value_parser(clap::value_parser!(std::path::PathBuf))
+        .required(false)
+        .action(clap::ArgAction::Set)
+    }
+
+    fn arg_launcher() -> clap::Arg {
+        arg!(--launcher <LAUNCHER> "Optional whitespace separated command prefix used to \
+                                    launch clang-format, e.g., a wrapper script. \
+                                    Overrides <JSON> configuration")
+        .required(false)
+        .action(clap::ArgAction::Set)
+    }
+
+    fn arg_jobs() -> clap::Arg {
+        arg!(-j --jobs ... "Optional parameter to define the number of jobs to use. \
+                            If provided without value (e.g., '-j') all available logical \
+                            cores are used. Maximum value is 255")
+        .required(false)
+        .num_args(0..=1)
+        .action(clap::ArgAction::Set)
+    }
+
     fn app() -> clap::Command {
         clap::Command::new(crate_name!())
             .arg_required_else_help(true)
             .version(crate_version!())
             .author(crate_authors!())
             .about(crate_description!())
-            .arg(
-                arg!(<JSON>)
-                    .help("Path/configuration as .json")
-                    .value_parser(clap::value_parser!(std::path::PathBuf)),
-            )
-            .arg(
-                arg!(-s --style ... "Optional path to .clang-format style file. \
-                                     Overrides <JSON> configuration")
-                .value_parser(clap::value_parser!(std::path::PathBuf))
-                .required(false)
-                .action(clap::ArgAction::Set),
-            )
-            .arg(
-                arg!(-c --command ... "Optional path to executable or clang-format command. \
-                                       Overrides <JSON> configuration, defaults to `clang-format`")
-                // .default_value("clang-format")
-                .value_parser(clap::value_parser!(std::path::PathBuf))
-                .required(false)
-                .action(clap::ArgAction::Set),
-            )
-            .arg(
-                arg!(-j --jobs ... "Optional parameter to define the number of jobs to use. \
-                                    If provided without value (e.g., '-j') all available logical \
-                                    cores are used. Maximum value is 255")
-                .required(false)
-                .num_args(0..=1)
-                .action(clap::ArgAction::Set),
-            )
+            .arg(Builder::arg_json())
+            .arg(Builder::arg_style())
+            .arg(Builder::arg_command())
+            .arg(Builder::arg_launcher())
+            .arg(Builder::arg_jobs())
             .arg(arg!(-v --verbose ... "Verbosity, use -vv... for verbose output.").global(true))
             .arg(
                 arg!(--check "Run in check mode instead of formatting. Use -vv to \
@@ -115,9 +279,16 @@ impl Builder {
                               Requires clang-format 10 or higher.")
                 .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                arg!(--fix "Formats the files that fail the check, without modifying any \
+                            other file. Fails if any file has been fixed. Requires --check.")
+                .action(clap::ArgAction::SetTrue)
+                .requires("check"),
+            )
             .arg(
                 arg!(-q --quiet "Suppress all output except for errors; overrides -v")
-                    .action(clap::ArgAction::SetTrue),
+                    .action(clap::ArgAction::SetTrue)
+                    .global(true),
             )
             .arg(
                 // See https://github.com/clap-rs/clap/issues/2468
@@ -130,11 +301,224 @@ impl Builder {
                      style file or style root directory is specified.")
                 .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                arg!(--"strict-style"
+                    "Fails if existing .clang-format or _clang-format files between the style \
+                     root directory and any of the selected files take precedence over the \
+                     placed style file. Such files are also reported with --strict-root. \
+                     This check is only available if a style root directory is specified.")
+                .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                arg!(--"warn-overlaps"
+                    "Warns about files that are matched by more than one entry in 'paths'. \
+                     Such files are always formatted only once, but overlapping entries may \
+                     indicate a misconfiguration.")
+                .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                arg!(--"list-changed"
+                    "Prints the files that have been changed by formatting to stdout, one path \
+                     per line and relative to the directory of the <JSON> configuration file. \
+                     Use together with -q to only print the changed files.")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("check"),
+            )
+            .arg(
+                arg!(--"emit-patch" <FILE> "Writes the changes to the patch file <FILE> instead \
+                                            of formatting the files in place. Paths in the patch \
+                                            are relative to the working directory, such that it \
+                                            can be applied using 'git apply'.")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .required(false)
+                .conflicts_with_all(["check", "list-changed"]),
+            )
+            .arg(
+                arg!(--"output-dir" <DIR> "Writes the formatted files into a tree below <DIR> \
+                                           that mirrors the paths relative to the directory of \
+                                           the <JSON> configuration file. The selected files are \
+                                           not modified.")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .required(false)
+                .conflicts_with_all(["check", "list-changed", "emit-patch"]),
+            )
+            .arg(
+                arg!(--"no-backup" "Does not keep a backup of the files that are changed by \
+                                    formatting, see the subcommand 'restore'")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["emit-patch", "output-dir"]),
+            )
+            .arg(
+                arg!(--"verify-idempotent" "Formats each file twice in memory and fails for \
+                                            files where the second pass changes the output \
+                                            again. The files are not modified.")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "check",
+                    "list-changed",
+                    "emit-patch",
+                    "output-dir",
+                    "no-backup",
+                ]),
+            )
+            .arg(
+                arg!(--"atomic-write" "Formats files via stdin instead of 'clang-format -i' and \
+                                       replaces changed files atomically, preserving their \
+                                       permissions. The owner is only preserved if permitted, \
+                                       i.e., replaced files of other users are owned by the \
+                                       executing user. Unchanged files are not written.")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["emit-patch", "output-dir", "verify-idempotent"]),
+            )
+            .arg(
+                arg!(--"stdin-filename" <PATH> "Reads the content of the file <PATH> from stdin \
+                                                and writes it to stdout, formatted if <PATH> is \
+                                                selected by the <JSON> configuration and \
+                                                unchanged otherwise. Requires clang-format 14 or \
+                                                higher for style files that are not placed.")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .required(false)
+                .conflicts_with_all([
+                    "check",
+                    "list-changed",
+                    "emit-patch",
+                    "output-dir",
+                    "verify-idempotent",
+                    "no-backup",
+                    "atomic-write",
+                ]),
+            )
+            .arg(
+                arg!([ARGS] ... "Additional arguments passed to clang-format, e.g., \
+                                 '-- --sort-includes=false'. Overrides <JSON> configuration")
+                .last(true)
+                .allow_hyphen_values(true),
+            )
             .subcommand_negates_reqs(true)
             .subcommand(
                 clap::Command::new("schema")
                     .about("Print the schema used for the <JSON> configuration file"),
             )
+            .subcommand(
+                clap::Command::new("init")
+                    .about(
+                        "Create a configuration file 'format.json' for the C-family sources \
+                         found in the given directory",
+                    )
+                    .arg(
+                        arg!([DIR])
+                            .help("Directory to scan, defaults to the working directory")
+                            .default_value(".")
+                            .value_parser(clap::value_parser!(std::path::PathBuf)),
+                    )
+                    .arg(
+                        arg!(--force "Overwrite an existing configuration file")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("list")
+                    .about(
+                        "Print the files selected by the <JSON> configuration, relative to the \
+                         directory of the configuration file, without executing clang-format",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(Builder::arg_style())
+                    .arg(arg!(--json "Print the file set as JSON").action(clap::ArgAction::SetTrue))
+                    .arg(
+                        arg!(--"show-filtered"
+                            "Also print the paths that have been removed by 'filterPre', \
+                             'filterPost', negated entries in 'paths' or 'extensions'")
+                        .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("explain")
+                    .about(
+                        "Explain why a file is or is not selected by the <JSON> configuration \
+                         and which style file is used by clang-format",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(
+                        arg!(<PATH>)
+                            .help("Path to the file, relative to the working directory")
+                            .value_parser(clap::value_parser!(std::path::PathBuf)),
+                    )
+                    .arg(Builder::arg_style()),
+            )
+            .subcommand(
+                clap::Command::new("validate")
+                    .about(
+                        "Validate the <JSON> configuration without formatting any files, e.g., \
+                         report unknown fields, patterns that do not match any path, and style \
+                         files that clang-format fails to parse",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(Builder::arg_style())
+                    .arg(Builder::arg_command())
+                    .arg(Builder::arg_launcher())
+                    .arg(
+                        arg!(--strict "Exit with an error if any warnings are reported")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("restore")
+                    .about(
+                        "Restore the files changed by the latest or the given run of the <JSON> \
+                         configuration from the backup",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(arg!([RUN_ID]).help("Identifier of the run, defaults to the latest run"))
+                    .arg(
+                        arg!(--list "List the available backups, latest first")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(
+                        arg!(--force "Also restore files that have been modified after formatting")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("doctor")
+                    .about(
+                        "Print the resolved clang-format executable, its version and \
+                         capabilities, and the style files that are used for the <JSON> \
+                         configuration",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(Builder::arg_style())
+                    .arg(Builder::arg_command())
+                    .arg(Builder::arg_launcher()),
+            )
+            .subcommand(
+                clap::Command::new("watch")
+                    .about(
+                        "Format or check the files selected by the <JSON> configuration, and \
+                         repeat for all files that change until interrupted",
+                    )
+                    .after_help(
+                        "New files are only picked up within the directories of the selected \
+                         files and the leading directories of the entries in 'paths', e.g., \
+                         'src' for 'src/**/*.c'. Files in other new directories are picked up \
+                         once the configuration file changes.",
+                    )
+                    .arg(Builder::arg_json())
+                    .arg(Builder::arg_style())
+                    .arg(Builder::arg_command())
+                    .arg(Builder::arg_launcher())
+                    .arg(Builder::arg_jobs())
+                    .arg(
+                        arg!(--check "Run in check mode instead of formatting")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(
+                        arg!(--interval <MS> "Interval in milliseconds for polling the files \
+                                              for changes")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .default_value("500"),
+                    ),
+            )
     }
 
     pub fn build() -> Builder {
@@ -152,14 +536,68 @@ impl Builder {
             process::exit(0);
         }
 
-        let json_path = self.path_for_key("JSON", true)?;
+        if let Some(matches) = self.matches.subcommand_matches("init") {
+            let dir = path_for_key(matches, "DIR", false)?;
+            crate::init::run(&dir, flag(matches, "force"))?;
+            process::exit(0);
+        }
+
+        // subcommands define their own <JSON> parameter and the options they support
+        let (matches, cmd) = match self.matches.subcommand() {
+            Some(("list", matches)) => (
+                matches,
+                Command::List(ListOptions {
+                    json: flag(matches, "json"),
+                    show_filtered: flag(matches, "show-filtered"),
+                }),
+            ),
+            Some(("validate", matches)) => (
+                matches,
+                Command::Validate {
+                    strict: flag(matches, "strict"),
+                },
+            ),
+            Some(("doctor", matches)) => (matches, Command::Doctor),
+            Some(("watch", matches)) => (
+                matches,
+                Command::Watch {
+                    check: flag(matches, "check"),
+                    interval: time::Duration::from_millis(
+                        value_of::<u64>(matches, "interval").copied().unwrap_or(500),
+                    ),
+                },
+            ),
+            Some(("restore", matches)) => (
+                matches,
+                Command::Restore {
+                    run_id: value_of::<String>(matches, "RUN_ID").cloned(),
+                    list: flag(matches, "list"),
+                    force: flag(matches, "force"),
+                },
+            ),
+            Some(("explain", matches)) => (
+                matches,
+                Command::Explain(path_for_key(matches, "PATH", false)?),
+            ),
+            _ => (
+                &self.matches,
+                if flag(&self.matches, "check") {
+                    Command::Check {
+                        fix: flag(&self.matches, "fix"),
+                    }
+                } else {
+                    Command::Format
+                },
+            ),
+        };
+
+        let json_path = path_for_key(matches, "JSON", true)?;
         let json = JsonModel::load(json_path).wrap_err("Invalid parameter for <JSON>")?;
 
-        let style = match self.matches.contains_id("style") {
+        let style = match value_of::<std::path::PathBuf>(matches, "style").is_some() {
             false => None,
             true => {
-                let style_path = self
-                    .path_for_key("style", true)
+                let style_path = path_for_key(matches, "style", true)
                     .wrap_err("Invalid parameter for option --style")?;
                 let path = utils::file_with_name_or_ext(style_path, ".clang-format")
                     .wrap_err("Invalid parameter for option --style")?;
@@ -167,10 +605,10 @@ impl Builder {
             }
         };
 
-        let command = match self.matches.get_one::<std::path::PathBuf>("command") {
+        let command = match value_of::<std::path::PathBuf>(matches, "command") {
             None => None,
             Some(_) => Some(
-                utils::executable_or_exists(self.path_for_key("command", false)?, None)
+                utils::executable_or_exists(path_for_key(matches, "command", false)?, None)
                     .wrap_err("Invalid parameter for option --command")
                     .suggestion(
                         "Please make sure that '--command' is either a valid absolute path, \
@@ -180,6 +618,24 @@ impl Builder {
             ),
         };
 
+        let launcher = match value_of::<String>(matches, "launcher") {
+            None => None,
+            Some(val) => {
+                let launcher: Vec<_> = val.split_whitespace().map(String::from).collect();
+                if launcher.is_empty() {
+                    return Err(eyre!("Invalid parameter for option --launcher"))
+                        .suggestion("Please provide a non-empty command prefix");
+                }
+                Some(launcher)
+            }
+        };
+
+        let args = matches
+            .try_get_many::<String>("ARGS")
+            .ok()
+            .flatten()
+            .map(|args| args.cloned().collect());
+
         // cannot use "and" since it is not lazily evaluated, and cannot use "and_then" nicely
         // since the question mark operator does not work in closures
         // let command = self
@@ -188,7 +644,7 @@ impl Builder {
         //     .and_then(|_| Some(self.path_for_key("command", false)?));
 
         let jobs = {
-            if let Some(val) = self.matches.get_one::<String>("jobs") {
+            if let Some(val) = value_of::<String>(matches, "jobs") {
                 let val: u8 = val
                     .parse()
                     .map_err(|_| eyre!("Invalid parameter for option --jobs"))
@@ -199,38 +655,75 @@ impl Builder {
             }
         };
 
-        let cmd = if self.matches.get_flag("check") {
-            Command::Check
-        } else {
-            Command::Format
-        };
-
-        let strict_root = self.matches.get_flag("strict-root");
+        let strict_root = flag(matches, "strict-root");
+        let strict_style = flag(matches, "strict-style");
+        let warn_overlaps = flag(matches, "warn-overlaps");
+        let list_changed = flag(matches, "list-changed");
+        let emit_patch = value_of::<path::PathBuf>(matches, "emit-patch").cloned();
+        let output_dir = value_of::<path::PathBuf>(matches, "output-dir").cloned();
+        let backup = !flag(matches, "no-backup");
+        let verify_idempotent = flag(matches, "verify-idempotent");
+        let atomic_write = flag(matches, "atomic-write");
+        // check mode only modifies files with --fix
+        if matches!(cmd, Command::Check { fix: false }) {
+            let ignored = [("--no-backup", !backup), ("--atomic-write", atomic_write)];
+            for (name, _) in ignored.iter().filter(|(_, set)| *set) {
+                log::warn!("{name} has no effect in check mode without --fix");
+            }
+        }
+        let stdin_filename = value_of::<path::PathBuf>(matches, "stdin-filename").cloned();
 
         Ok(Data {
             json,
             style,
             command,
+            launcher,
+            args,
             jobs,
             cmd,
             strict_root,
+            strict_style,
+            warn_overlaps,
+            list_changed,
+            emit_patch,
+            output_dir,
+            backup,
+            verify_idempotent,
+            atomic_write,
+            stdin_filename,
         })
     }
+}
 
-    fn path_for_key(&self, key: &str, check_exists: bool) -> eyre::Result<path::PathBuf> {
-        let path = self
-            .matches
-            .get_one::<std::path::PathBuf>(key)
-            .map(std::path::PathBuf::from)
-            .ok_or(eyre!(format!(
-                "Could not convert parameter '{key}' to path"
-            )))?;
-
-        if check_exists {
-            return utils::path_or_err(path);
-        }
-        Ok(path)
+/// Returns the value of the argument `id`, or `None` if the argument is not defined for the
+/// (sub)command that produced `matches`.
+fn value_of<'a, T>(matches: &'a clap::ArgMatches, id: &str) -> Option<&'a T>
+where
+    T: std::any::Any + Clone + Send + Sync + 'static,
+{
+    matches.try_get_one::<T>(id).ok().flatten()
+}
+
+fn flag(matches: &clap::ArgMatches, id: &str) -> bool {
+    value_of::<bool>(matches, id).copied().unwrap_or(false)
+}
+
+fn path_for_key(
+    matches: &clap::ArgMatches,
+    key: &str,
+    check_exists: bool,
+) -> eyre::Result<path::PathBuf> {
+    let path = matches
+        .get_one::<std::path::PathBuf>(key)
+        .map(std::path::PathBuf::from)
+        .ok_or(eyre!(format!(
+            "Could not convert parameter '{key}' to path"
+        )))?;
+
+    if check_exists {
+        return utils::path_or_err(path);
     }
+    Ok(path)
 }
 
 impl JsonModel {
@@ -239,7 +732,17 @@ impl JsonModel {
         serde_json::to_string_pretty(&schema).unwrap()
     }
 
-    fn load(path: impl AsRef<path::Path>) -> eyre::Result<JsonModel> {
+    /// Names of all fields of the configuration file, as specified in the schema.
+    pub fn field_names() -> Vec<String> {
+        let schema = schema_for!(JsonModel);
+        schema
+            .schema
+            .object
+            .map(|object| object.properties.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn load(path: impl AsRef<path::Path>) -> eyre::Result<JsonModel> {
         let json_path = utils::file_with_ext(path.as_ref(), "json", true)?;
         let json_name = json_path.to_string_lossy();
 
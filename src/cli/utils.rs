@@ -211,6 +211,51 @@ where
     filename_or_exists_with_ext(path, root, ext)
 }
 
+/// Searches the directories in the `PATH` environment variable for the executable `name`, which is
+/// how the operating system resolves commands that are specified by their name only.
+pub fn search_path<P>(name: P) -> Option<path::PathBuf>
+where
+    P: AsRef<path::Path>,
+{
+    let ext = if cfg!(windows) { Some("exe") } else { None };
+    let dirs = std::env::var_os("PATH")?;
+    std::env::split_paths(&dirs).find_map(|dir| {
+        let mut candidate = dir.join(name.as_ref());
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if let Some(ext) = ext {
+            candidate.set_extension(ext);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        None
+    })
+}
+
+/// Provides `path` relative to `root`, using `..` for paths outside of `root`, i.e., in the same
+/// way as paths are specified in the configuration file. Both paths are expected to be canonical.
+pub fn relative_to(root: &path::Path, path: &path::Path) -> path::PathBuf {
+    let common = root
+        .components()
+        .zip(path.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // paths on different drives or prefixes do not have a relative representation
+    if common == 0 {
+        return path::PathBuf::from(path);
+    }
+
+    let mut rel = path::PathBuf::new();
+    for _ in root.components().skip(common) {
+        rel.push("..");
+    }
+    rel.extend(path.components().skip(common));
+    rel
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +267,22 @@ mod tests {
 
         assert_eq!(".clang-format", file_name.to_lowercase());
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_relative_to() {
+        let root = path::Path::new("/project/config");
+        assert_eq!(
+            path::PathBuf::from("src/main.c"),
+            relative_to(root, path::Path::new("/project/config/src/main.c"))
+        );
+        assert_eq!(
+            path::PathBuf::from("../src/main.c"),
+            relative_to(root, path::Path::new("/project/src/main.c"))
+        );
+        assert_eq!(
+            path::PathBuf::from("../../other/main.c"),
+            relative_to(root, path::Path::new("/other/main.c"))
+        );
+    }
 }
@@ -1,4 +1,12 @@
-use std::{io, path, process, str::FromStr};
+use std::{
+    fs,
+    io::{self, Write},
+    path, process,
+    str::FromStr,
+    thread,
+};
+
+use crate::style::StyleSource;
 
 #[derive(Debug, Clone)]
 struct Version {
@@ -22,10 +30,79 @@ impl FromStr for Version {
     }
 }
 
+/// Options that are controlled by the tool and can therefore not be passed as extra arguments.
+/// Options restricting the formatted range, e.g., `--lines`, would apply to all files of a run.
+const RESERVED_ARGS: [&str; 14] = [
+    "i",
+    "n",
+    "dry-run",
+    "dump-config",
+    "files",
+    "help",
+    "help-list",
+    "version",
+    "output-replacements-xml",
+    "assume-filename",
+    "lines",
+    "offset",
+    "length",
+    "cursor",
+];
+
+/// Options that are set by default but can be overridden using extra arguments.
+const DEFAULT_ARGS: [(&str, &str); 3] = [
+    ("fallback-style", "-fallback-style=none"),
+    ("style", "-style=file"),
+    ("Werror", "--Werror"),
+];
+
+/// Name of an option without leading dashes and value, e.g., `style` for `--style=file`.
+fn arg_name(arg: &str) -> &str {
+    let arg = arg.trim_start_matches('-');
+    match arg.split_once('=') {
+        Some((name, _)) => name,
+        None => arg,
+    }
+}
+
 #[derive(Debug)]
 pub struct Runner {
     cmd: path::PathBuf,
+    launcher: Vec<String>,
+    args: Vec<String>,
     version: Option<Version>,
+    version_output: Option<String>,
+    atomic_write: bool,
+}
+
+/// Writes `content` to the temporary file in the directory of `path` and renames it to `path`,
+/// such that the file is never partially written. The permissions of `path` are preserved, and
+/// also its owner and group where permitted, i.e., typically only the group unless executed by a
+/// privileged user.
+fn write_atomic(path: &path::Path, content: &[u8]) -> Result<(), io::Error> {
+    let metadata = fs::metadata(path)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => path::Path::new("."),
+    };
+
+    let mut tmp = tempfile::Builder::new()
+        .prefix(".run-clang-format")
+        .tempfile_in(dir)?;
+    tmp.write_all(content)?;
+    tmp.as_file().sync_all()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{fchown, MetadataExt};
+        // changing the owner fails for unprivileged users, but the group may still be preserved
+        if fchown(tmp.as_file(), Some(metadata.uid()), Some(metadata.gid())).is_err() {
+            let _ = fchown(tmp.as_file(), None, Some(metadata.gid()));
+        }
+    }
+    // the permissions are set after changing the owner, which may clear the setuid bit
+    fs::set_permissions(tmp.path(), metadata.permissions())?;
+    tmp.persist(path).map_err(|err| err.error)?;
+    Ok(())
 }
 
 impl Runner {
@@ -34,17 +111,91 @@ impl Runner {
         P: AsRef<path::Path>,
     {
         let cmd = path::PathBuf::from(path.as_ref());
-        Runner { cmd, version: None }
+        Runner {
+            cmd,
+            launcher: vec![],
+            args: vec![],
+            version: None,
+            version_output: None,
+            atomic_write: false,
+        }
+    }
+
+    /// Sets additional arguments that are passed to each invocation of `run_format` and
+    /// `run_check`. Arguments replace the default options `-fallback-style`, `-style` and
+    /// `--Werror` of the same name. Values must be passed as `--option=value`, since separate
+    /// values cannot be told apart from file names. Options that are controlled by the tool,
+    /// e.g., `-i` or `--dry-run`, as well as file names are rejected.
+    pub fn set_args(&mut self, args: Vec<String>) -> Result<(), io::Error> {
+        for arg in args.iter() {
+            if !arg.starts_with('-') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unsupported argument '{arg}', values must be passed as '--option=value' \
+                         and file names cannot be passed as arguments"
+                    ),
+                ));
+            }
+            if RESERVED_ARGS.contains(&arg_name(arg)) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unsupported argument '{arg}', this option is controlled by the tool"),
+                ));
+            }
+        }
+        self.args = args;
+        Ok(())
+    }
+
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    fn format_args(&self) -> Vec<&str> {
+        let mut args: Vec<_> = DEFAULT_ARGS
+            .iter()
+            .filter(|(name, _)| !self.args.iter().any(|arg| arg_name(arg) == *name))
+            .map(|(_, arg)| *arg)
+            .collect();
+        args.extend(self.args.iter().map(String::as_str));
+        args
+    }
+
+    /// Sets an argv prefix that is used for each invocation of the command, e.g., a wrapper
+    /// script or `["nix", "shell", "nixpkgs#clang-tools", "-c"]`.
+    pub fn set_launcher(&mut self, launcher: Vec<String>) {
+        self.launcher = launcher;
+    }
+
+    pub fn get_launcher(&self) -> &[String] {
+        &self.launcher
+    }
+
+    /// Formats files via stdin and writes the result using an atomic rename, instead of
+    /// executing clang-format with `-i`. Files are only written if the content changed.
+    pub fn set_atomic_write(&mut self, atomic_write: bool) {
+        self.atomic_write = atomic_write;
+    }
+
+    fn command(&self) -> process::Command {
+        match self.launcher.split_first() {
+            None => process::Command::new(self.cmd.as_path()),
+            Some((launcher, args)) => {
+                let mut cmd = process::Command::new(launcher);
+                cmd.args(args).arg(self.cmd.as_os_str());
+                cmd
+            }
+        }
     }
 
     fn eval_status(status: process::ExitStatus) -> Result<(), io::Error> {
         match status.code() {
             Some(0) => (),
             Some(code) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Process terminated with code {code}"),
-                ));
+                return Err(io::Error::other(format!(
+                    "Process terminated with code {code}"
+                )));
             }
             None => {
                 return Err(io::Error::new(
@@ -62,14 +213,17 @@ impl Runner {
             .map(|v| format!("{}.{}.{}", v.major, v.minor, v.patch))
     }
 
+    /// Provides the raw output of `--version`, available after a successful `validate`.
+    pub fn get_version_output(&self) -> Option<&str> {
+        self.version_output.as_deref()
+    }
+
     pub fn get_path(&self) -> path::PathBuf {
         self.cmd.clone()
     }
 
     pub fn validate(&mut self) -> Result<(), io::Error> {
-        let cmd = process::Command::new(self.cmd.as_path())
-            .arg("--version")
-            .output()?;
+        let cmd = self.command().arg("--version").output()?;
 
         if let Err(err) = Runner::eval_status(cmd.status) {
             log::error!(
@@ -82,20 +236,17 @@ impl Runner {
         // example output of clang-format:
         // clang-format version 4.0.0 (tags/checker/checker-279)
         let stdout = String::from_utf8_lossy(&cmd.stdout);
+        self.version_output = Some(stdout.trim().to_string());
 
         self.version = Some(stdout.parse::<Version>().map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to parse --version output {stdout}: {err}"),
-            )
+            io::Error::other(format!("Failed to parse --version output {stdout}: {err}"))
         })?);
         Ok(())
     }
 
     pub fn supports_check_or_err(&self) -> Result<(), io::Error> {
         if self.version.is_none() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
+            return Err(io::Error::other(
                 "Unknown version, --dry-run requires \
                 clang-format version 10.0.1 or higher",
             ));
@@ -104,14 +255,11 @@ impl Runner {
         // https://github.com/llvm/llvm-project/commit/6a1f7d6c9ff8228328d0e65b8678a9c6dff49837
         let version = self.version.as_ref().unwrap();
         if version.major < 9u8 {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!(
-                    "Invalid version {}, --dry-run check requires \
-                    clang-format version 10.0.1 or higher",
-                    self.get_version().unwrap()
-                ),
-            ));
+            return Err(io::Error::other(format!(
+                "Invalid version {}, --dry-run check requires \
+                clang-format version 10.0.1 or higher",
+                self.get_version().unwrap()
+            )));
         }
 
         Ok(())
@@ -123,11 +271,8 @@ impl Runner {
         if let Err(err) = Runner::eval_status(output.status) {
             let stderr = String::from_utf8_lossy(&output.stderr);
 
-            if stderr.len() != 0 {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("{err}\n---\n{stderr}---"),
-                ));
+            if !stderr.is_empty() {
+                return Err(io::Error::other(format!("{err}\n---\n{stderr}---")));
             }
             return Err(err);
         }
@@ -138,13 +283,14 @@ impl Runner {
     where
         P: AsRef<path::Path>,
     {
-        let mut cmd = process::Command::new(self.cmd.as_path());
+        if self.atomic_write {
+            return self.run_format_as(file.as_ref(), file.as_ref());
+        }
+        let mut cmd = self.command();
 
         // execute clang-format to edit in place, using style file
         cmd.arg(file.as_ref().as_os_str())
-            .arg("-fallback-style=none")
-            .arg("-style=file")
-            .arg("--Werror")
+            .args(self.format_args())
             .arg("-i");
 
         Runner::run(cmd)
@@ -155,23 +301,245 @@ impl Runner {
         P: AsRef<path::Path>,
     {
         self.supports_check_or_err()?;
-        let mut cmd = process::Command::new(self.cmd.as_path());
+        let mut cmd = self.command();
 
         cmd.arg(file.as_ref().as_os_str())
-            .arg("-fallback-style=none")
-            .arg("-style=file")
-            .arg("--Werror")
+            .args(self.format_args())
             .arg("--dry-run");
 
         Runner::run(cmd)
     }
+
+    /// Executes the given command and passes `content` via stdin, providing the output that
+    /// `clang-format` produced on stdout.
+    fn run_stdin(mut cmd: process::Command, content: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut child = cmd
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()?;
+
+        // stdin is written in a separate thread such that large outputs do not lead to a
+        // deadlock due to a full stdout pipe.
+        let mut stdin = child.stdin.take().expect("stdin must be piped");
+        let content = content.to_vec();
+        let writer = thread::spawn(move || stdin.write_all(&content));
+
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .map_err(|_| io::Error::other("Failed to write to stdin"))??;
+
+        if let Err(err) = Runner::eval_status(output.status) {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            if !stderr.is_empty() {
+                return Err(io::Error::other(format!("{err}\n---\n{stderr}---")));
+            }
+            return Err(err);
+        }
+        Ok(output.stdout)
+    }
+
+    /// Provides the style options that clang-format uses for `assume_filename`, as reported by
+    /// `--dump-config`. The file itself does not need to exist, it is only used to determine the
+    /// language and the location of the style file.
+    pub fn dump_config<P>(&self, assume_filename: P) -> Result<String, io::Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut cmd = self.command();
+        let mut assume = std::ffi::OsString::from("--assume-filename=");
+        assume.push(assume_filename.as_ref().as_os_str());
+
+        cmd.args(self.format_args())
+            .arg("--dump-config")
+            .arg(assume);
+        let stdout = Runner::run_stdin(cmd, &[])?;
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    }
+
+    /// Formats `content` via stdin, using `assume_filename` to determine the language and the
+    /// location of the style file. Provides the formatted content without modifying any file.
+    pub fn format_stdin(
+        &self,
+        content: &[u8],
+        assume_filename: &path::Path,
+    ) -> Result<Vec<u8>, io::Error> {
+        let mut cmd = self.command();
+        let mut assume = std::ffi::OsString::from("--assume-filename=");
+        assume.push(assume_filename.as_os_str());
+
+        cmd.args(self.format_args()).arg(assume);
+        Runner::run_stdin(cmd, content)
+    }
+
+    /// Formats `content` via stdin using the given `style`, e.g., as resolved by
+    /// [`crate::style_source`], and provides the formatted content. The `assume_filename`
+    /// determines the language and does not need to exist. Style files are passed explicitly,
+    /// thus they do not need to be placed in the `styleRoot` directory for clang-format 14 or
+    /// higher. Older versions only support existing style files, which are looked up relative
+    /// to `assume_filename`.
+    pub fn format_buffer<P>(
+        &self,
+        content: &[u8],
+        assume_filename: P,
+        style: StyleSource,
+    ) -> Result<Vec<u8>, io::Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        // https://reviews.llvm.org/D72326, `--style=file:<path>` is available since version 14
+        let style_file_arg = self.version.as_ref().is_some_and(|v| v.major >= 14);
+        let style = match style {
+            StyleSource::Placed { style_file, .. } | StyleSource::Existing { style_file }
+                if style_file_arg =>
+            {
+                format!("--style=file:{}", style_file.to_string_lossy())
+            }
+            StyleSource::Existing { .. } => "-style=file".to_string(),
+            StyleSource::Placed { style_file, .. } => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "Cannot use the style file '{}' without placing it, \
+                         this requires clang-format version 14 or higher",
+                        style_file.to_string_lossy()
+                    ),
+                ))
+            }
+            StyleSource::Argument { style } | StyleSource::Fallback { style } => {
+                format!("--style={style}")
+            }
+        };
+
+        let mut cmd = self.command();
+        let mut assume = std::ffi::OsString::from("--assume-filename=");
+        assume.push(assume_filename.as_ref().as_os_str());
+
+        let args = self.format_args();
+        cmd.args(args.into_iter().filter(|arg| arg_name(arg) != "style"))
+            .arg(style)
+            .arg(assume);
+        Runner::run_stdin(cmd, content)
+    }
+
+    /// Formats the `file` in place via stdin, using `assume_filename` to determine the language
+    /// and the location of the style file. The file is only written if the content changed.
+    pub fn run_format_as<P, Q>(&self, file: P, assume_filename: Q) -> Result<(), io::Error>
+    where
+        P: AsRef<path::Path>,
+        Q: AsRef<path::Path>,
+    {
+        let content = fs::read(file.as_ref())?;
+        let formatted = self.format_stdin(&content, assume_filename.as_ref())?;
+
+        if formatted != content {
+            match self.atomic_write {
+                true => write_atomic(file.as_ref(), &formatted)?,
+                false => fs::write(file.as_ref(), formatted)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the format of the `file` via stdin, using `assume_filename` to determine the
+    /// language and the location of the style file.
+    pub fn run_check_as<P, Q>(&self, file: P, assume_filename: Q) -> Result<(), io::Error>
+    where
+        P: AsRef<path::Path>,
+        Q: AsRef<path::Path>,
+    {
+        let content = fs::read(file.as_ref())?;
+        let formatted = self.format_stdin(&content, assume_filename.as_ref())?;
+
+        if formatted != content {
+            return Err(io::Error::other(format!(
+                "{}: code should be clang-formatted (formatted as '{}')",
+                file.as_ref().to_string_lossy(),
+                assume_filename.as_ref().to_string_lossy()
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl Clone for Runner {
     fn clone(&self) -> Runner {
         Runner {
             cmd: path::PathBuf::from(self.cmd.as_path()),
+            launcher: self.launcher.clone(),
+            args: self.args.clone(),
             version: self.version.clone(),
+            version_output: self.version_output.clone(),
+            atomic_write: self.atomic_write,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        let mut cmd = Runner::new("clang-format");
+        assert_eq!(
+            vec!["-fallback-style=none", "-style=file", "--Werror"],
+            cmd.format_args()
+        );
+
+        cmd.set_args(vec![
+            "--fallback-style=LLVM".into(),
+            "--sort-includes=false".into(),
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                "-style=file",
+                "--Werror",
+                "--fallback-style=LLVM",
+                "--sort-includes=false"
+            ],
+            cmd.format_args()
+        );
+
+        assert!(cmd.set_args(vec!["-i".into()]).is_err());
+        assert!(cmd.set_args(vec!["--dry-run".into()]).is_err());
+        assert!(cmd.set_args(vec!["-n".into()]).is_err());
+        assert!(cmd.set_args(vec!["main.c".into()]).is_err());
+        assert!(cmd.set_args(vec!["--lines=1:2".into()]).is_err());
+        assert!(cmd
+            .set_args(vec!["--fallback-style".into(), "LLVM".into()])
+            .is_err());
+        // the previously configured arguments remain untouched
+        assert_eq!(2, cmd.get_args().len());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_atomic() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let tmp = tempfile::Builder::new().prefix("atomic").tempdir().unwrap();
+        let file = tmp.path().join("file.c");
+        fs::write(&file, "int a;  \n").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+        // the owner can only be changed by privileged users
+        let owner = match std::os::unix::fs::chown(&file, Some(4711), Some(4711)) {
+            Ok(_) => (4711, 4711),
+            Err(_) => {
+                let metadata = fs::metadata(&file).unwrap();
+                (metadata.uid(), metadata.gid())
+            }
+        };
+
+        write_atomic(&file, b"int a;\n").unwrap();
+        assert_eq!("int a;\n", fs::read_to_string(&file).unwrap());
+        let metadata = fs::metadata(&file).unwrap();
+        assert_eq!(0o640, metadata.permissions().mode() & 0o777);
+        assert_eq!(owner, (metadata.uid(), metadata.gid()));
+        // no temporary files are left behind
+        assert_eq!(1, fs::read_dir(tmp.path()).unwrap().count());
+    }
+}
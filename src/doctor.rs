@@ -0,0 +1,228 @@
+use std::{collections::BTreeSet, path};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{
+    cli::{self, utils::relative_to},
+    cmd,
+    explain::label,
+    globs, resolve, style,
+};
+
+/// Provides the path of the executable that is used for the command `cmd`. Commands that are
+/// specified by their name only are searched in the `PATH` environment variable.
+fn resolve_executable(cmd: &path::Path) -> Option<path::PathBuf> {
+    let is_name = cmd.file_name().is_some_and(|name| name == cmd.as_os_str());
+    let path = match is_name {
+        true => cli::utils::search_path(cmd)?,
+        false => path::PathBuf::from(cmd),
+    };
+    path.canonicalize().ok()
+}
+
+/// Capabilities of the clang-format executable that are used by the tool.
+fn capabilities(cmd: &cmd::Runner) -> Vec<(&'static str, Result<(), String>)> {
+    let dump_config = tempfile::Builder::new()
+        .prefix("run-clang-format")
+        .tempdir()
+        .map_err(|err| format!("{err}"))
+        .and_then(|dir| {
+            cmd.dump_config(dir.path().join("doctor.cpp"))
+                .map(|_| ())
+                .map_err(|err| format!("{err}"))
+        });
+
+    vec![
+        (
+            "--check",
+            cmd.supports_check_or_err().map_err(|err| format!("{err}")),
+        ),
+        ("--dump-config", dump_config),
+    ]
+}
+
+/// Directories from which clang-format searches for style files: The directory of the
+/// configuration file, the `styleRoot` and the root directories of all entries in `paths`.
+fn config_roots(
+    data: &cli::Data,
+    style_and_root: &Option<(path::PathBuf, path::PathBuf)>,
+) -> Vec<path::PathBuf> {
+    let mut roots = vec![data.json.root.clone()];
+    if let Some((_, style_root)) = style_and_root {
+        roots.push(style_root.clone());
+    }
+    if let Ok(candidates) =
+        globs::build_matchers_from(&data.json.paths, &data.json.root, "paths", &data.json.name)
+    {
+        roots.extend(
+            candidates
+                .iter()
+                .filter(|m| !m.negated)
+                .filter_map(|m| path::PathBuf::from(m.matcher.root()).canonicalize().ok()),
+        );
+    }
+
+    let mut unique = BTreeSet::new();
+    roots.retain(|root| unique.insert(root.clone()));
+    roots
+}
+
+/// Style files that exist in `dir` or any of its parent directories.
+fn style_files_above(dir: &path::Path) -> Vec<path::PathBuf> {
+    dir.ancestors()
+        .flat_map(|dir| style::STYLE_FILE_NAMES.iter().map(|name| dir.join(name)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Checks that the style file can be placed in the `styleRoot` directory.
+fn check_style_root(style_root: &path::Path) -> Result<(), String> {
+    let metadata = style_root.metadata().map_err(|err| format!("{err}"))?;
+    if metadata.permissions().readonly() {
+        return Err("the directory is read-only".to_string());
+    }
+    // permissions do not cover ACLs, mounts, etc., thus creating a file is the only reliable check
+    tempfile::Builder::new()
+        .prefix(".run-clang-format")
+        .tempfile_in(style_root)
+        .map(|_| ())
+        .map_err(|err| format!("{err}"))
+}
+
+/// Prints diagnostics about the clang-format executable and the style files that are used for
+/// the configuration.
+pub fn run(data: &cli::Data) -> eyre::Result<()> {
+    let mut warnings = vec![];
+    let root = &data.json.root;
+    let rel = |path: &path::Path| match relative_to(root, path) {
+        rel if rel.as_os_str().is_empty() => ".".to_string(),
+        rel => rel.to_string_lossy().to_string(),
+    };
+
+    println!("{} {}", label("Config"), data.json.name);
+
+    // clang-format executable
+    match resolve::command(data) {
+        Err(err) => warnings.push(format!("{err:#}")),
+        Ok(cmd) => {
+            let resolved = match resolve_executable(&cmd) {
+                Some(path) => path.to_string_lossy().to_string(),
+                // the command may only exist within the environment of the launcher
+                None if resolve::has_launcher(data) => "resolved by the launcher".to_string(),
+                None => {
+                    warnings.push(format!(
+                        "The command '{}' could not be found",
+                        cmd.to_string_lossy()
+                    ));
+                    "not found".to_string()
+                }
+            };
+            println!(
+                "{} {} ({})",
+                label("Command"),
+                cmd.to_string_lossy(),
+                resolved
+            );
+        }
+    }
+    if let Ok(launcher) = resolve::launcher(data) {
+        if !launcher.is_empty() {
+            println!("{} {}", label("Launcher"), launcher.join(" "));
+        }
+    }
+
+    match crate::get_command(data) {
+        Err(err) => {
+            println!("{} unknown", label("Version"));
+            warnings.push(format!("{err:#}"));
+        }
+        Ok(cmd) => {
+            let version = cmd.get_version_output().unwrap_or_default();
+            println!("{} {}", label("Version"), version);
+
+            let mut lines = vec![];
+            for (name, result) in capabilities(&cmd) {
+                let result = match result {
+                    Ok(_) => "supported".to_string(),
+                    Err(err) => {
+                        warnings.push(format!("'{name}' is not supported: {err}"));
+                        "not supported".to_string()
+                    }
+                };
+                lines.push(format!("  {name}: {result}"));
+            }
+            println!("{}\n{}", label("Capabilities"), lines.join("\n"));
+        }
+    }
+
+    // style files
+    let style_and_root = resolve::style_and_root(data);
+    if let Err(err) = &style_and_root {
+        warnings.push(format!("{err:#}"));
+    }
+    let style_and_root = style_and_root.unwrap_or(None);
+
+    let mut lines = vec![];
+    for dir in config_roots(data, &style_and_root) {
+        lines.push(format!("  {}", rel(&dir)));
+        let files = style_files_above(&dir);
+        if files.is_empty() {
+            lines.push("    no style files".to_string());
+        }
+        lines.extend(files.iter().map(|file| format!("    {}", rel(file))));
+    }
+    println!("{}\n{}", label("Style files"), lines.join("\n"));
+
+    match &style_and_root {
+        None => println!("{} not configured", label("styleRoot")),
+        Some((style_file, style_root)) => {
+            let result = match check_style_root(style_root) {
+                Ok(_) => "writable".to_string(),
+                Err(err) => {
+                    warnings.push(format!(
+                        "The style file cannot be placed in the 'styleRoot' directory {}: {err}",
+                        style_root.to_string_lossy()
+                    ));
+                    "not writable".to_string()
+                }
+            };
+            println!("{} {} ({})", label("styleRoot"), rel(style_root), result);
+            println!("{} {}", label("styleFile"), rel(style_file));
+
+            // an existing style file in the style root must match the placed one
+            let dst = style_root.join(style::STYLE_FILE_NAMES[0]);
+            if dst.is_file() && std::fs::read(&dst).ok() != std::fs::read(style_file).ok() {
+                warnings.push(format!(
+                    "The existing style file {} does not match the style file {}",
+                    rel(&dst),
+                    rel(style_file)
+                ));
+            }
+
+            // style files within the style root take precedence over the placed style file
+            match crate::select_paths(data) {
+                Err(err) => warnings.push(format!("{err:#}")),
+                Ok(selection) => {
+                    let shadowing = style::shadowing(selection.paths.iter(), &style_and_root);
+                    for (existing, paths) in shadowing {
+                        warnings.push(format!(
+                            "The style file {} shadows the placed style file for {} files",
+                            rel(&existing),
+                            paths.len()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if warnings.is_empty() {
+        log::info!("No problems found");
+        return Ok(());
+    }
+    for warning in warnings.iter() {
+        log::warn!("{}", warning);
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::path;
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{
+    cli::{self, utils::relative_to},
+    globs, resolve, style,
+};
+
+/// How an entry in `paths` matches a file.
+enum EntryMatch {
+    File,
+    /// The entry matches a parent directory of the file.
+    Directory(path::PathBuf),
+}
+
+fn entry_match(entry: &globs::PathMatcher<'_>, file: &path::Path) -> Option<EntryMatch> {
+    // the file is canonical, thus the root of the matcher must be canonical as well
+    let root = path::PathBuf::from(entry.matcher.root())
+        .canonicalize()
+        .ok()?;
+    let rel = file.strip_prefix(&root).ok()?;
+
+    if entry.matcher.is_match(rel.to_path_buf()) {
+        return Some(EntryMatch::File);
+    }
+    rel.ancestors()
+        .skip(1)
+        .filter(|p| !p.as_os_str().is_empty())
+        .find(|p| entry.matcher.is_match(p.to_path_buf()))
+        .map(|p| EntryMatch::Directory(root.join(p)))
+}
+
+pub(crate) fn label(name: &str) -> String {
+    format!("{}", console::style(format!("{name:<12}")).bold())
+}
+
+/// Explains why the file `file` is or is not selected by the configuration, and which style file
+/// clang-format uses for it.
+pub fn run(data: &cli::Data, file: &path::Path) -> eyre::Result<()> {
+    let file_name = file.to_string_lossy();
+    let path = file
+        .canonicalize()
+        .wrap_err(format!("Invalid path '{file_name}'"))
+        .suggestion("Please provide the path to an existing file")?;
+
+    let style_and_root = resolve::style_and_root(data)?;
+    let selection = crate::select_paths(data)?;
+    let options = resolve::walk_options(data);
+
+    let root = &data.json.root;
+    let rel = |path: &path::Path| format!("{}", relative_to(root, path).to_string_lossy());
+
+    let candidates =
+        globs::build_matchers_from(&data.json.paths, &data.json.root, "paths", &data.json.name)?;
+    let filter_pre =
+        globs::build_glob_set_from(&data.json.filter_pre, "preFilter", &data.json.name)?;
+    let filter_post =
+        globs::build_glob_set_from(&data.json.filter_post, "postFilter", &data.json.name)?;
+
+    println!("{} {}", label("File"), rel(&path));
+
+    // entries in 'paths'
+    let matches: Vec<_> = candidates.iter().map(|m| entry_match(m, &path)).collect();
+    let mut lines = vec![];
+    for (candidate, entry) in data.json.paths.iter().zip(matches.iter()) {
+        let result = match entry {
+            None => "no match".to_string(),
+            Some(EntryMatch::File) => "matches".to_string(),
+            Some(EntryMatch::Directory(dir)) => format!("matches directory {}", rel(dir)),
+        };
+        lines.push(format!("  {candidate}: {result}"));
+    }
+    println!("{}\n{}", label("paths"), lines.join("\n"));
+
+    // the pre-filter is evaluated for the file and all parent directories below the root of any
+    // matching entry, i.e., all paths that are visited while walking the file tree
+    let walked: Vec<_> = candidates
+        .iter()
+        .zip(matches.iter())
+        .filter(|(m, entry)| !m.negated && entry.is_some())
+        .filter_map(|(m, _)| path::PathBuf::from(m.matcher.root()).canonicalize().ok())
+        .flat_map(|walk_root| {
+            path.ancestors()
+                .take_while(|p| p.starts_with(&walk_root))
+                .map(path::PathBuf::from)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let pre = filter_pre.as_ref().and_then(|globs| {
+        walked.iter().find_map(|p| {
+            globs
+                .iter()
+                .find(|glob| glob.is_match(p))
+                .map(|glob| (glob.glob(), p))
+        })
+    });
+    let pre = match pre {
+        None => "no match".to_string(),
+        Some((glob, p)) => format!("'{glob}' matches {}", rel(p)),
+    };
+    println!("{} {}", label("filterPre"), pre);
+
+    let hidden = match options.skips_hidden(filter_pre.is_some()) {
+        false => None,
+        true => walked.iter().find(|p| globmatch::is_hidden_entry(p)),
+    };
+    if let Some(hidden) = hidden {
+        println!(
+            "{} {} is hidden and skipped, see 'includeHidden'",
+            label("hidden"),
+            rel(hidden)
+        );
+    }
+    let symlink = options.follow_symlinks == cli::FollowSymlinks::Never && file.is_symlink();
+    if symlink {
+        println!(
+            "{} {} is a symlink and skipped, see 'followSymlinks'",
+            label("symlink"),
+            file_name
+        );
+    }
+
+    let post = filter_post
+        .as_ref()
+        .and_then(|globs| globs.iter().find(|glob| glob.is_match(&path)));
+    let post = match post {
+        None => "no match".to_string(),
+        Some(glob) => format!("'{}' matches", glob.glob()),
+    };
+    println!("{} {}", label("filterPost"), post);
+
+    if let Some(extensions) = &data.json.extensions {
+        let result = match globs::has_extension(&path, extensions) {
+            true => "listed",
+            false => "not listed",
+        };
+        println!("{} {}", label("extensions"), result);
+    }
+
+    match &style_and_root {
+        None => println!("{} not configured", label("styleRoot")),
+        Some((_, style_root)) => {
+            let result = match path.starts_with(style_root) {
+                true => "inside",
+                false => "outside",
+            };
+            println!("{} {} {}", label("styleRoot"), result, rel(style_root));
+        }
+    }
+
+    let style = style::StyleSource::resolve(&path, &style_and_root, &resolve::args(data))
+        .map_paths(|p| relative_to(root, p));
+    println!("{} {}", label("Style"), style);
+
+    // the selection is the final authority, the above details only explain the decision
+    let filtered = selection
+        .filtered
+        .iter()
+        .find(|(p, filter)| *p == path || (*filter == globs::Filter::Pre && path.starts_with(p)));
+    let verdict = if selection.paths.contains(&path) {
+        console::style("selected".to_string()).green().bold()
+    } else {
+        let reason = match filtered {
+            Some((_, globs::Filter::Pre)) => "skipped by 'filterPre'",
+            Some((_, globs::Filter::Negated)) => "excluded by a negated entry in 'paths'",
+            Some((_, globs::Filter::Post)) => "removed by 'filterPost'",
+            Some((_, globs::Filter::Extension)) => "extension not listed in 'extensions'",
+            None if path.is_dir() => "directories are not formatted",
+            None if walked.is_empty() => "no entry in 'paths' matches",
+            None if hidden.is_some() => "hidden paths are skipped",
+            None if symlink => "symlinks are skipped",
+            None if options.respect_ignore_files => "ignored by an ignore file",
+            None => "skipped while searching the file tree",
+        };
+        console::style(format!("not selected, {reason}"))
+            .red()
+            .bold()
+    };
+    println!("{} {}", label("Result"), verdict);
+    Ok(())
+}
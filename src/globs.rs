@@ -1,7 +1,14 @@
-use std::path;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs, path,
+    sync::{Arc, Mutex},
+};
 
 #[allow(unused_imports)]
 use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::Serialize;
+
+use crate::cli;
 
 fn wrap_result<T>(result: eyre::Result<T>, field: &str, file: &str) -> eyre::Result<T> {
     result
@@ -11,23 +18,73 @@ fn wrap_result<T>(result: eyre::Result<T>, field: &str, file: &str) -> eyre::Res
         ))
 }
 
+/// A [`globmatch::Matcher`] for an entry in `paths`.
+///
+/// Entries starting with `!` are negated: instead of adding paths they exclude all paths that
+/// match the pattern, as well as all paths within matching directories.
+#[derive(Debug)]
+pub struct PathMatcher<'a> {
+    pub matcher: globmatch::Matcher<'a, path::PathBuf>,
+    pub negated: bool,
+}
+
+impl PathMatcher<'_> {
+    /// Checks whether the path or any of its parent directories within the root of the matcher
+    /// is a match for the pattern.
+    pub fn is_match(&self, path: &path::Path) -> bool {
+        let root = self.matcher.root();
+        match path.strip_prefix(&root) {
+            Err(_) => false,
+            Ok(rel) => rel
+                .ancestors()
+                .filter(|p| !p.as_os_str().is_empty())
+                .any(|p| self.matcher.is_match(p.to_path_buf())),
+        }
+    }
+}
+
 pub fn build_matchers_from<'a, P>(
     globs: &'a [String],
     root: P,
     field: &str,
     file: &str,
-) -> eyre::Result<Vec<globmatch::Matcher<'a, path::PathBuf>>>
+) -> eyre::Result<Vec<PathMatcher<'a>>>
 where
     P: AsRef<path::Path>,
 {
     // https://stackoverflow.com/a/33217302/7281683
-    let globs: Vec<_> = globs.iter().map(|s| &**s).collect();
+    let (globs, negated): (Vec<_>, Vec<_>) = globs
+        .iter()
+        .map(|s| match s.strip_prefix('!') {
+            Some(glob) => (glob, true),
+            None => (&**s, false),
+        })
+        .unzip();
 
-    wrap_result(
+    let matchers = wrap_result(
         globmatch::wrappers::build_matchers(&globs, root).map_err(|err| eyre!(err)),
         field,
         file,
-    )
+    )?;
+
+    Ok(matchers
+        .into_iter()
+        .zip(negated)
+        .map(|(matcher, negated)| PathMatcher { matcher, negated })
+        .collect())
+}
+
+/// Evaluates all patterns in order, similar to `.gitignore` files: the last matching pattern
+/// decides whether a path is included, i.e., a later pattern can re-include a path that has been
+/// excluded by a preceding negated pattern.
+fn is_included(patterns: &[PathMatcher<'_>], path: &path::Path) -> bool {
+    patterns.iter().fold(false, |included, pattern| {
+        if pattern.is_match(path) {
+            !pattern.negated
+        } else {
+            included
+        }
+    })
 }
 
 pub fn build_glob_set_from<'a>(
@@ -46,21 +103,446 @@ pub fn build_glob_set_from<'a>(
     )
 }
 
-pub fn match_paths<P>(
-    candidates: Vec<globmatch::Matcher<'_, P>>,
-    filter: Option<Vec<globmatch::GlobSet<'_>>>,
+pub fn has_extension(path: &path::Path, extensions: &[String]) -> bool {
+    let name = match path.file_name().and_then(std::ffi::OsStr::to_str) {
+        None => return false,
+        Some(name) => name,
+    };
+
+    extensions.iter().any(|ext| {
+        let ext = format!(".{}", ext.trim_start_matches('.'));
+        if cfg!(windows) {
+            name.to_lowercase().ends_with(&ext.to_lowercase())
+        } else {
+            name.ends_with(&ext)
+        }
+    })
+}
+
+/// Options used for walking the file tree.
+#[derive(Debug, Default, Clone)]
+pub struct WalkOptions {
+    /// Skip paths that are ignored by `.gitignore`, `.git/info/exclude` and `.clang-format-ignore`.
+    pub respect_ignore_files: bool,
+    /// Explicit policy for hidden paths. If `None`, hidden paths are only skipped if no
+    /// pre-filter has been provided.
+    pub include_hidden: Option<bool>,
+    pub follow_symlinks: cli::FollowSymlinks,
+    pub broken_symlinks: cli::BrokenSymlinks,
+    /// Only walk the directories that contain this canonical path, such that a single path can
+    /// be evaluated without walking the entire file tree. The path does not need to exist, e.g.,
+    /// for new files, as long as its parent directory exists.
+    pub only: Option<path::PathBuf>,
+}
+
+impl WalkOptions {
+    /// Checks whether hidden paths are skipped, which depends on the presence of a pre-filter.
+    pub fn skips_hidden(&self, has_filter_pre: bool) -> bool {
+        match self.include_hidden {
+            Some(include_hidden) => !include_hidden,
+            None => !has_filter_pre,
+        }
+    }
+}
+
+/// Pre-filter applied while walking the file tree.
+///
+/// The walker requires a filter with a `'static` lifetime, thus the matchers of the pre-filter
+/// are cloned into this type.
+#[derive(Debug, Clone)]
+struct EntryFilter {
+    skip_hidden: bool,
+    skip_symlinks: bool,
+    patterns: Vec<globset::GlobSet>,
+}
+
+impl EntryFilter {
+    fn new(filter_pre: &Option<Vec<globmatch::GlobSet<'_>>>, options: &WalkOptions) -> Self {
+        EntryFilter {
+            skip_hidden: options.skips_hidden(filter_pre.is_some()),
+            skip_symlinks: options.follow_symlinks == cli::FollowSymlinks::Never,
+            patterns: filter_pre
+                .as_ref()
+                .map(|filter| filter.iter().map(|glob| glob.matcher.clone()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn is_entry(&self, path: &path::Path) -> bool {
+        if self.skip_hidden && globmatch::is_hidden_entry(path) {
+            return false;
+        }
+        if self.skip_symlinks && path.is_symlink() {
+            return false;
+        }
+        self.excluded_by(path).is_none()
+    }
+
+    /// Provides the index of the first pattern of the pre-filter that excludes the path.
+    fn excluded_by(&self, path: &path::Path) -> Option<usize> {
+        self.patterns.iter().position(|glob| glob.is_match(path))
+    }
+}
+
+/// Extracts the path from an error of the walker, if any.
+fn error_path(err: &ignore::Error) -> Option<&path::Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
+        }
+        _ => None,
+    }
+}
+
+fn is_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_loop(err),
+        _ => false,
+    }
+}
+
+fn is_broken_symlink(path: &path::Path) -> bool {
+    path.is_symlink() && fs::metadata(path).is_err()
+}
+
+/// Checks whether nothing exists at the path, not even a broken symlink.
+fn is_new(path: &path::Path) -> bool {
+    fs::symlink_metadata(path).is_err()
+}
+
+/// Paths found by walking the file tree.
+#[derive(Debug, Default)]
+struct Walked {
+    paths: Vec<path::PathBuf>,
+    /// Files and directories skipped due to a match with the pre-filter, with the index of the
+    /// matching pattern. The contents of such directories have not been searched.
+    excluded: Vec<(path::PathBuf, usize)>,
+}
+
+/// Walks the file tree below `root` (including `root`), applying the pre-filter.
+///
+/// Broken symlinks are part of the returned list since they are only handled for paths that
+/// would have been selected.
+fn walk(
+    root: &path::Path,
+    filter_pre: &Option<Vec<globmatch::GlobSet<'_>>>,
+    options: &WalkOptions,
+) -> Walked {
+    let filter = EntryFilter::new(filter_pre, options);
+    if !filter.is_entry(root) {
+        let excluded = match filter.excluded_by(root) {
+            Some(idx) => vec![(path::PathBuf::from(root), idx)],
+            None => vec![],
+        };
+        return Walked {
+            paths: vec![],
+            excluded,
+        };
+    }
+
+    // the root may contain relative path components (e.g., "path/../root"), which would lead to
+    // the wrong parent directories when searching for ignore files. the tree is therefore walked
+    // for the canonical path and all entries are mapped back to the provided root.
+    let canonical_root = match root.canonicalize() {
+        Ok(path) => path,
+        Err(_) => return Walked::default(),
+    };
+    if let Some(only) = &options.only {
+        if !only.starts_with(&canonical_root) {
+            return Walked::default();
+        }
+    }
+    let to_root = |path: &path::Path| -> Option<path::PathBuf> {
+        path.strip_prefix(&canonical_root)
+            .ok()
+            .map(|rel| root.join(rel))
+    };
+
+    let filter_root = path::PathBuf::from(root);
+    let filter_base = canonical_root.clone();
+    // the filter of the walker must be 'static, excluded entries are therefore shared via Arc
+    let excluded = Arc::new(Mutex::new(vec![]));
+    let filter_excluded = Arc::clone(&excluded);
+    let only = options.only.clone();
+    let new_filter = filter.clone();
+
+    let mut builder = ignore::WalkBuilder::new(&canonical_root);
+    builder
+        .standard_filters(false)
+        .git_ignore(options.respect_ignore_files)
+        .git_exclude(options.respect_ignore_files)
+        .parents(options.respect_ignore_files)
+        .follow_links(options.follow_symlinks == cli::FollowSymlinks::All)
+        .filter_entry(move |entry| match entry.path().strip_prefix(&filter_base) {
+            Ok(_)
+                if only
+                    .as_ref()
+                    .is_some_and(|only| !only.starts_with(entry.path())) =>
+            {
+                false
+            }
+            Ok(rel) => {
+                let path = filter_root.join(rel);
+                let is_entry = filter.is_entry(&path);
+                if let (false, Some(idx)) = (is_entry, filter.excluded_by(&path)) {
+                    filter_excluded.lock().unwrap().push((path, idx));
+                }
+                is_entry
+            }
+            Err(_) => false,
+        });
+
+    if options.respect_ignore_files {
+        builder.add_custom_ignore_filename(".clang-format-ignore");
+    }
+
+    let mut paths: Vec<_> = builder
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(entry) => to_root(entry.path()),
+            Err(err) => {
+                if is_loop(&err) {
+                    log::warn!("Skipping symlink cycle: {err}");
+                    return None;
+                }
+                // when following symlinks, broken links are reported as errors
+                match error_path(&err) {
+                    Some(path) if is_broken_symlink(path) => to_root(path),
+                    _ => {
+                        log::debug!("Skipping path: {err}");
+                        None
+                    }
+                }
+            }
+        })
+        .collect();
+
+    // a new file is never reported by the walker, it is an entry if its directory has been walked
+    if let Some(only) = options.only.as_ref().filter(|only| is_new(only)) {
+        let new = (only.parent().and_then(to_root), to_root(only));
+        if let (Some(parent), Some(path)) = new {
+            if paths.contains(&parent)
+                && new_filter.is_entry(&path)
+                && !(options.respect_ignore_files && is_ignored(only))
+            {
+                paths.push(path);
+            }
+        }
+    }
+
+    let excluded = std::mem::take(&mut *excluded.lock().unwrap());
+    Walked { paths, excluded }
+}
+
+/// Leading path of the matcher's pattern without any glob syntax, e.g., `root/src` for the
+/// pattern `src/**/*.c`. Only paths within this directory can match the pattern.
+pub fn literal_prefix(matcher: &globmatch::Matcher<'_, path::PathBuf>) -> path::PathBuf {
+    let mut prefix = path::PathBuf::from(matcher.root());
+    for component in path::Path::new(matcher.rest()).components() {
+        if component
+            .as_os_str()
+            .to_string_lossy()
+            .contains(['*', '?', '[', '{'])
+        {
+            break;
+        }
+        prefix.push(component);
+    }
+    prefix
+}
+
+/// Walks the file tree for the given matcher, applying the pre-filter.
+///
+/// Excluded directories within the literal prefix of the pattern are reported since their
+/// contents are unknown, excluded files only if they match the pattern.
+fn walk_matcher(
+    matcher: &globmatch::Matcher<'_, path::PathBuf>,
+    filter_pre: &Option<Vec<globmatch::GlobSet<'_>>>,
+    options: &WalkOptions,
+) -> Walked {
+    let root = path::PathBuf::from(matcher.root());
+    let prefix = literal_prefix(matcher);
+    let is_match = |path: &path::PathBuf| match path.strip_prefix(&root) {
+        Ok(rel) => matcher.is_match(rel.to_path_buf()),
+        Err(_) => false,
+    };
+
+    let walked = walk(&root, filter_pre, options);
+    Walked {
+        paths: walked.paths.into_iter().filter(is_match).collect(),
+        excluded: walked
+            .excluded
+            .into_iter()
+            .filter(|(path, _)| (path.is_dir() && path.starts_with(&prefix)) || is_match(path))
+            .collect(),
+    }
+}
+
+/// Reason for removing a path from the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Filter {
+    /// Skipped by a pattern in `filterPre`; for directories, none of their contents was searched.
+    Pre,
+    /// Excluded by a negated entry in `paths`.
+    Negated,
+    /// Removed by a pattern in `filterPost`.
+    Post,
+    /// The extension is not listed in `extensions`.
+    Extension,
+}
+
+impl Filter {
+    /// Name of the filter, used for both the text and the JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Filter::Pre => "filterPre",
+            Filter::Negated => "negated",
+            Filter::Post => "filterPost",
+            Filter::Extension => "extensions",
+        }
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Result of matching the configured paths.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Canonical paths of all selected files.
+    pub paths: Vec<path::PathBuf>,
+    /// Canonical paths that have been removed from the selection, with the reason for removal.
+    pub filtered: Vec<(path::PathBuf, Filter)>,
+    /// Files that are matched by more than one entry in `paths`, with the matching entries.
+    pub overlaps: Vec<(path::PathBuf, Vec<String>)>,
+    /// Number of matches for each pattern of the configuration.
+    pub hits: Hits,
+}
+
+/// Number of paths matched by each pattern, in the order of the patterns in the configuration.
+/// The numbers include directories and paths that are removed later on, e.g., a match of an entry
+/// in `paths` can still be removed by `filterPost`.
+#[derive(Debug, Default)]
+pub struct Hits {
+    pub paths: Vec<usize>,
+    pub filter_pre: Vec<usize>,
+    pub filter_post: Vec<usize>,
+}
+
+pub fn match_paths(
+    candidates: Vec<PathMatcher<'_>>,
+    filter_pre: Option<Vec<globmatch::GlobSet<'_>>>,
     filter_post: Option<Vec<globmatch::GlobSet<'_>>>,
-) -> (Vec<path::PathBuf>, Vec<path::PathBuf>)
-where
-    P: AsRef<path::Path>,
-{
-    let (paths, filtered) = globmatch::wrappers::match_paths(candidates, filter, filter_post);
+    extensions: &Option<Vec<String>>,
+    options: &WalkOptions,
+) -> eyre::Result<Selection> {
+    // only positive patterns are used for walking the file tree. for each path, the indices
+    // of the matching entries are tracked for detecting overlaps.
+    let mut origins: HashMap<path::PathBuf, BTreeSet<usize>> = HashMap::new();
+    let mut filtered = vec![];
+    let mut hits = Hits {
+        paths: vec![0; candidates.len()],
+        filter_pre: vec![0; filter_pre.as_ref().map_or(0, |f| f.len())],
+        filter_post: vec![0; filter_post.as_ref().map_or(0, |f| f.len())],
+    };
+    let mut add_excluded = |filtered: &mut Vec<_>, excluded: Vec<(path::PathBuf, usize)>| {
+        for (path, idx) in excluded {
+            hits.filter_pre[idx] += 1;
+            filtered.push((path, Filter::Pre));
+        }
+    };
+
+    for (idx, m) in candidates.iter().enumerate().filter(|(_, m)| !m.negated) {
+        let walked = walk_matcher(&m.matcher, &filter_pre, options);
+        hits.paths[idx] = walked.paths.len();
+        for path in walked.paths {
+            origins.entry(path).or_default().insert(idx);
+        }
+        add_excluded(&mut filtered, walked.excluded);
+    }
+    let mut paths: Vec<_> = origins.keys().cloned().collect();
+    paths.sort_unstable();
+
+    // with an allow-list for extensions it is possible to specify plain directories in 'paths',
+    // which are expanded into all paths below them. since the paths are sorted, parent
+    // directories are expanded before their children, which can then be skipped.
+    if extensions.is_some() {
+        let mut expanded: Vec<path::PathBuf> = vec![];
+        paths = paths
+            .into_iter()
+            .flat_map(|path| {
+                if !path.is_dir() {
+                    return vec![path];
+                }
+                // symlinks to directories are only expanded if all symlinks should be followed
+                if path.is_symlink() && options.follow_symlinks != cli::FollowSymlinks::All {
+                    return vec![path];
+                }
+                if expanded.iter().any(|dir| path.starts_with(dir)) {
+                    return vec![];
+                }
+                expanded.push(path.clone());
+                let walked = walk(&path, &filter_pre, options);
+                add_excluded(&mut filtered, walked.excluded);
+                walked.paths
+            })
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+    }
+
+    // negated patterns are evaluated after expanding directories such that excluding a directory
+    // also excludes all of its files
+    let paths: Vec<_> = if candidates.iter().any(|m| m.negated) {
+        paths
+            .into_iter()
+            .filter(|path| {
+                for (idx, m) in candidates.iter().enumerate().filter(|(_, m)| m.negated) {
+                    if m.is_match(path) {
+                        hits.paths[idx] += 1;
+                    }
+                }
+                let included = is_included(&candidates, path);
+                if !included {
+                    filtered.push((path.clone(), Filter::Negated));
+                }
+                included
+            })
+            .collect()
+    } else {
+        paths
+    };
+
+    let paths: Vec<_> = paths
+        .into_iter()
+        .filter(|path| match &filter_post {
+            None => true,
+            Some(patterns) => match patterns.iter().position(|glob| glob.is_match(path)) {
+                None => true,
+                Some(idx) => {
+                    hits.filter_post[idx] += 1;
+                    filtered.push((path.clone(), Filter::Post));
+                    false
+                }
+            },
+        })
+        .collect();
 
     log::debug!(
         "paths \n{}",
         paths
             .iter()
-            .map(|p| format!("{}", p.canonicalize().unwrap().to_string_lossy()))
+            .map(|p| format!("{}", canonical_or_self(p).to_string_lossy()))
             .collect::<Vec<_>>()
             .join("\n")
     );
@@ -70,23 +552,363 @@ where
             "filtered \n{}",
             filtered
                 .iter()
-                .map(|p| format!("{}", p.canonicalize().unwrap().to_string_lossy()))
+                .map(|(p, _)| format!("{}", canonical_or_self(p).to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    // accept only files, directories are silently ignored
+    let mut selected = vec![];
+    let mut broken = vec![];
+    for path in paths.into_iter().filter(|path| !path.is_dir()) {
+        if let Some(extensions) = extensions {
+            if !has_extension(&path, extensions) {
+                filtered.push((path, Filter::Extension));
+                continue;
+            }
+        }
+        let is_only = |only: &path::PathBuf| canonical_or_self(&path) == *only;
+        if path.is_file() || (is_new(&path) && options.only.as_ref().is_some_and(is_only)) {
+            selected.push(path);
+        } else if is_broken_symlink(&path) {
+            broken.push(path);
+        }
+    }
+
+    if !broken.is_empty() {
+        let list = broken
+            .iter()
+            .map(|p| format!("{}", p.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match options.broken_symlinks {
+            cli::BrokenSymlinks::Skip => log::debug!("skipped broken symlinks \n{list}"),
+            cli::BrokenSymlinks::Warn => log::warn!("Skipping broken symlinks:\n{list}"),
+            cli::BrokenSymlinks::Error => {
+                return Err(eyre!("Found {} broken symlinks:\n{list}", broken.len())).suggestion(
+                    "Please fix or remove the broken symlinks, or configure the field \
+                     'brokenSymlinks' to skip them",
+                );
+            }
+        }
+    }
+
+    // the same file may have been matched via different paths, e.g., "path/../file" and "file",
+    // or via symlinks. the paths are therefore de-duplicated using their canonical path.
+    let mut canonical: BTreeMap<path::PathBuf, BTreeSet<usize>> = BTreeMap::new();
+    for path in selected.iter() {
+        let mut entries = origins.get(path).cloned().unwrap_or_default();
+        if extensions.is_some() {
+            // files of expanded directories are matched by the entries of all parents
+            for dir in path.ancestors().skip(1) {
+                if let Some(dir_entries) = origins.get(dir) {
+                    entries.extend(dir_entries);
+                }
+            }
+        }
+        canonical
+            .entry(canonical_or_self(path))
+            .or_default()
+            .extend(entries);
+    }
+
+    let overlaps: Vec<_> = canonical
+        .iter()
+        .filter(|(_, entries)| entries.len() > 1)
+        .map(|(path, entries)| {
+            let globs: Vec<_> = entries
+                .iter()
+                .map(|idx| candidates[*idx].matcher.glob().to_string())
+                .collect();
+            (path.clone(), globs)
+        })
+        .collect();
+
+    if !overlaps.is_empty() {
+        log::debug!(
+            "overlapping \n{}",
+            overlaps
+                .iter()
+                .map(|(p, globs)| format!("{} <- {}", p.to_string_lossy(), globs.join(", ")))
                 .collect::<Vec<_>>()
                 .join("\n")
         );
     }
 
-    let paths = paths
+    let mut filtered: Vec<_> = filtered
         .into_iter()
-        .filter(|path| path.as_path().is_file())
-        .collect(); // accept only files
+        .map(|(path, filter)| (canonical_or_self(&path), filter))
+        .collect();
+    filtered.sort_unstable();
+    filtered.dedup_by(|a, b| a.0 == b.0);
+
+    Ok(Selection {
+        paths: canonical.into_keys().collect(),
+        filtered,
+        overlaps,
+        hits,
+    })
+}
+
+fn canonical_or_self(path: &path::Path) -> path::PathBuf {
+    path.canonicalize()
+        .ok()
+        .or_else(|| canonical_parent(path))
+        .unwrap_or_else(|| path::PathBuf::from(path))
+}
+
+/// Canonical path of a file that does not need to exist, e.g., a new file: The canonical path of
+/// the parent directory joined with the file name. Relative paths are resolved relative to the
+/// current working directory.
+pub fn canonical_parent(path: &path::Path) -> Option<path::PathBuf> {
+    let (parent, name) = (path.parent()?, path.file_name()?);
+    path::Path::new(".")
+        .join(parent)
+        .canonicalize()
+        .ok()
+        .map(|parent| parent.join(name))
+}
 
-    (paths, filtered)
+/// Checks whether the ignore files in the parent directories of `path` exclude the path, for
+/// files that do not exist and are therefore never reported by the walker. The deepest matching
+/// pattern decides, as for the walker.
+fn is_ignored(path: &path::Path) -> bool {
+    let in_git = path.ancestors().any(|dir| dir.join(".git").exists());
+    for dir in path.ancestors().skip(1) {
+        let mut files = vec![dir.join(".clang-format-ignore")];
+        if in_git {
+            files.push(dir.join(".gitignore"));
+            files.push(dir.join(".git/info/exclude"));
+        }
+        for file in files.iter().filter(|file| file.is_file()) {
+            let (ignore, _) = ignore::gitignore::Gitignore::new(file);
+            match ignore.matched(path, false) {
+                ignore::Match::Ignore(_) => return true,
+                ignore::Match::Whitelist(_) => return false,
+                ignore::Match::None => (),
+            }
+        }
+    }
+    false
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+
+    #[test]
+    fn test_negated() -> eyre::Result<()> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let globs = vec![
+            "test-files/c-demo/**/*.c".to_string(),
+            "!test-files/c-demo/pkg_b".to_string(),
+            "test-files/c-demo/pkg_b/module_c/*.c".to_string(),
+            "!**/module_a.c".to_string(),
+        ];
+
+        let filter_pre = Some(vec![".*".to_string()]);
+
+        let candidates = build_matchers_from(&globs, root, "paths", "test")?;
+        let filter_pre = build_glob_set_from(&filter_pre, "filterPre", "test")?;
+        let selection = match_paths(candidates, filter_pre, None, &None, &WalkOptions::default())?;
+        let (paths, filtered) = (selection.paths, selection.filtered);
+
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+
+        assert!(!names.contains(&"module_a.c".to_string()));
+        assert!(!names.contains(&"module_b.c".to_string()));
+        assert!(names.contains(&"module_c.c".to_string()));
+        assert!(names.contains(&"main.c".to_string()));
+        let reasons: Vec<_> = filtered
+            .iter()
+            .map(|(p, filter)| {
+                (
+                    p.file_name().unwrap().to_string_lossy().to_string(),
+                    *filter,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (".hidden".to_string(), Filter::Pre),
+                ("module_a.c".to_string(), Filter::Negated),
+                ("module_b.c".to_string(), Filter::Negated),
+            ],
+            reasons
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_filtered_within_prefix() -> eyre::Result<()> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        // the matcher for a plain directory walks its parent, which contains '.hidden'
+        let globs = vec!["test-files/c-demo/pkg_a".to_string()];
+        let filter_pre = Some(vec![".*".to_string()]);
+        let extensions = Some(vec!["c".to_string()]);
+
+        let candidates = build_matchers_from(&globs, root, "paths", "test")?;
+        let filter_pre = build_glob_set_from(&filter_pre, "filterPre", "test")?;
+        let selection = match_paths(
+            candidates,
+            filter_pre,
+            None,
+            &extensions,
+            &WalkOptions::default(),
+        )?;
+
+        assert_eq!(1, selection.paths.len());
+        assert!(selection
+            .filtered
+            .iter()
+            .all(|(path, _)| path.to_string_lossy().contains("pkg_a")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_only() -> eyre::Result<()> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let globs = vec![
+            "test-files/c-demo/**/*.c".to_string(),
+            "!**/module_b.c".to_string(),
+        ];
+        let candidates = || build_matchers_from(&globs, root, "paths", "test");
+        let demo = path::Path::new(root)
+            .join("test-files/c-demo")
+            .canonicalize()?;
+
+        for (file, selected) in [
+            ("pkg_a/module_a/module_a.c", true),
+            ("pkg_a/module_a/module_a.h", false),
+            ("pkg_b/module_b/module_b.c", false),
+        ] {
+            let options = WalkOptions {
+                only: Some(demo.join(file)),
+                ..Default::default()
+            };
+            let selection = match_paths(candidates()?, None, None, &None, &options)?;
+            let expected = match selected {
+                true => vec![demo.join(file)],
+                false => vec![],
+            };
+            assert_eq!(expected, selection.paths);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_overlaps() -> eyre::Result<()> {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let globs = vec![
+            "test-files/c-demo/**/*.c".to_string(),
+            "test-files/c-demo/project/src/main.c".to_string(),
+            "test-files/c-demo/pkg_a/../project/src/*.c".to_string(),
+        ];
+
+        let candidates = build_matchers_from(&globs, root, "paths", "test")?;
+        let selection = match_paths(candidates, None, None, &None, &WalkOptions::default())?;
+
+        let main: Vec<_> = selection
+            .paths
+            .iter()
+            .filter(|p| p.ends_with("main.c"))
+            .collect();
+        assert_eq!(1, main.len());
+
+        assert_eq!(1, selection.overlaps.len());
+        let (path, entries) = &selection.overlaps[0];
+        assert!(path.ends_with("project/src/main.c"));
+        assert_eq!(3, entries.len());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn match_tmp(dir: &path::Path, options: &WalkOptions) -> eyre::Result<Vec<String>> {
+        let globs = vec!["**/*.c".to_string()];
+        let candidates = build_matchers_from(&globs, dir, "paths", "test")?;
+        let selection = match_paths(candidates, None, None, &None, options)?;
+
+        // selected paths are canonical, thus the links are reported relative to the canonical dir
+        let dir = dir.canonicalize()?;
+        Ok(selection
+            .paths
+            .iter()
+            .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().to_string())
+            .collect())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_hidden_and_symlinks() -> eyre::Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let tmp = tempfile::Builder::new().prefix("globs").tempdir()?;
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("src/.hidden"))?;
+        fs::create_dir_all(dir.join("ext"))?;
+        fs::write(dir.join("src/a.c"), "")?;
+        fs::write(dir.join("src/.hidden/b.c"), "")?;
+        fs::write(dir.join("ext/c.c"), "")?;
+        symlink(dir.join("src/a.c"), dir.join("src/link.c"))?;
+        symlink(dir.join("ext"), dir.join("src/ext"))?;
+        symlink(dir.join("src"), dir.join("ext/cycle"))?;
+
+        // "src/link.c" resolves to "src/a.c" and is therefore only selected once
+        let options = WalkOptions::default();
+        assert_eq!(vec!["ext/c.c", "src/a.c"], match_tmp(dir, &options)?);
+
+        let options = WalkOptions {
+            include_hidden: Some(true),
+            follow_symlinks: cli::FollowSymlinks::Never,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec!["ext/c.c", "src/.hidden/b.c", "src/a.c"],
+            match_tmp(dir, &options)?
+        );
+
+        // the cycle ext/cycle -> src -> src/ext -> ext is detected and skipped
+        let options = WalkOptions {
+            follow_symlinks: cli::FollowSymlinks::All,
+            ..Default::default()
+        };
+        assert_eq!(vec!["ext/c.c", "src/a.c"], match_tmp(dir, &options)?);
+
+        symlink(dir.join("i/do/not/exist.c"), dir.join("src/broken.c"))?;
+        for follow_symlinks in [cli::FollowSymlinks::Files, cli::FollowSymlinks::All] {
+            let mut options = WalkOptions {
+                follow_symlinks,
+                ..Default::default()
+            };
+            assert!(match_tmp(dir, &options).is_ok());
+            options.broken_symlinks = cli::BrokenSymlinks::Error;
+            assert!(match_tmp(dir, &options).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_extension() {
+        let extensions = vec!["c".to_string(), ".h".to_string(), "cu.in".to_string()];
+
+        assert!(has_extension(path::Path::new("some/file.c"), &extensions));
+        assert!(has_extension(path::Path::new("some/file.h"), &extensions));
+        assert!(has_extension(
+            path::Path::new("some/file.cu.in"),
+            &extensions
+        ));
+        assert!(!has_extension(path::Path::new("some/file.in"), &extensions));
+        assert!(!has_extension(
+            path::Path::new("some/file.cpp"),
+            &extensions
+        ));
+        assert!(!has_extension(path::Path::new("some/c"), &extensions));
+    }
 
     #[test]
     fn test_foreach() {
@@ -0,0 +1,271 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::Serialize;
+
+use crate::{cli, style::STYLE_FILE_NAMES};
+
+/// Name of the configuration file created by `init`.
+const CONFIG_NAME: &str = "format.json";
+
+/// Extensions of C-family sources and headers that are considered when scanning the tree.
+const SOURCE_EXTENSIONS: [&str; 17] = [
+    "c", "h", "cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx", "h++", "inl", "ipp", "tpp", "m", "mm",
+    "cu", "cuh",
+];
+
+/// Patterns for the pre-filter of the created configuration. Matching directories are also
+/// skipped while scanning the tree.
+const FILTER_PRE: [&str; 10] = [
+    ".*",
+    "build",
+    "build-*",
+    "cmake-build-*",
+    "out",
+    "third_party",
+    "thirdparty",
+    "vendor",
+    "external",
+    "node_modules",
+];
+
+/// Configuration file created by `init`, a subset of [`cli::JsonModel`].
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    paths: Vec<String>,
+    filter_pre: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style_root: Option<String>,
+}
+
+/// Sources and style files found within the scanned directory, relative to the directory.
+#[derive(Debug, Default)]
+struct Scan {
+    sources: Vec<path::PathBuf>,
+    styles: Vec<path::PathBuf>,
+}
+
+fn is_style_file(name: &str) -> bool {
+    STYLE_FILE_NAMES.contains(&name) || name.ends_with(".clang-format")
+}
+
+fn source_extension(name: &str) -> Option<&'static str> {
+    let (_, ext) = name.rsplit_once('.')?;
+    SOURCE_EXTENSIONS
+        .iter()
+        .copied()
+        .find(|known| *known == ext)
+}
+
+fn scan(dir: &path::Path) -> eyre::Result<Scan> {
+    let mut excludes = globset::GlobSetBuilder::new();
+    for pattern in FILTER_PRE {
+        excludes.add(globset::Glob::new(pattern)?);
+    }
+    let excludes = excludes.build()?;
+
+    let walker = ignore::WalkBuilder::new(dir)
+        .standard_filters(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !is_dir || !excludes.is_match(entry.file_name())
+        })
+        .build();
+
+    let mut result = Scan::default();
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let rel = match entry.path().strip_prefix(dir) {
+            Ok(rel) => path::PathBuf::from(rel),
+            Err(_) => continue,
+        };
+        let name = entry.file_name().to_string_lossy();
+        if is_style_file(&name) {
+            result.styles.push(rel);
+        } else if source_extension(&name).is_some() {
+            result.sources.push(rel);
+        }
+    }
+    result.sources.sort();
+    result.styles.sort();
+    Ok(result)
+}
+
+/// Escapes glob metacharacters in a literal path component.
+fn escape(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '*' | '?' | '[' | ']' | '{' | '}' => format!("[{c}]"),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Proposes one glob per top-level folder, matching all source extensions found in the folder.
+fn propose_paths(sources: &[path::PathBuf]) -> Vec<String> {
+    let mut groups: BTreeMap<Option<String>, BTreeSet<&str>> = BTreeMap::new();
+    for source in sources {
+        let name = source.file_name().unwrap_or_default().to_string_lossy();
+        let ext = match source_extension(&name) {
+            None => continue,
+            Some(ext) => ext,
+        };
+        let folder = match source.components().count() {
+            1 => None,
+            _ => source
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().to_string()),
+        };
+        groups.entry(folder).or_default().insert(ext);
+    }
+
+    groups
+        .into_iter()
+        .map(|(folder, extensions)| {
+            let extensions: Vec<_> = extensions.into_iter().map(escape).collect();
+            let pattern = match extensions.len() {
+                1 => format!("*.{}", extensions[0]),
+                _ => format!("*.{{{}}}", extensions.join(",")),
+            };
+            match folder {
+                None => pattern,
+                Some(folder) => format!("{}/**/{pattern}", escape(&folder)),
+            }
+        })
+        .collect()
+}
+
+/// Proposes the `styleFile` and `styleRoot`. A style file in the root directory is picked up by
+/// clang-format, no configuration is needed. Otherwise the style file closest to the root is
+/// proposed, which is placed in the root directory when executing.
+fn propose_style(styles: &[path::PathBuf]) -> Option<(String, String)> {
+    let has_root_style = styles.iter().any(|p| {
+        STYLE_FILE_NAMES
+            .iter()
+            .any(|name| p == path::Path::new(name))
+    });
+    if has_root_style {
+        return None;
+    }
+    styles
+        .iter()
+        .min_by_key(|p| (p.components().count(), p.to_path_buf()))
+        .map(|p| {
+            // the configuration uses forward slashes on all platforms
+            let file = p
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (file, ".".to_string())
+        })
+}
+
+fn propose(scan: &Scan) -> Config {
+    let style = propose_style(&scan.styles);
+    let (style_file, style_root) = match style {
+        None => (None, None),
+        Some((file, root)) => (Some(file), Some(root)),
+    };
+    Config {
+        paths: propose_paths(&scan.sources),
+        filter_pre: FILTER_PRE.iter().map(|s| s.to_string()).collect(),
+        style_file,
+        style_root,
+    }
+}
+
+/// Scans `dir` for C-family sources and style files and creates a configuration file.
+pub fn run(dir: &path::Path, force: bool) -> eyre::Result<()> {
+    let dir_name = dir.to_string_lossy();
+    let dir = cli::utils::dir_or_err(dir)
+        .wrap_err("Invalid directory for 'init'")
+        .suggestion("Please provide an existing directory")?;
+
+    let dst = dir.join(CONFIG_NAME);
+    let dst_name = dst.to_string_lossy().to_string();
+    if dst.exists() && !force {
+        return Err(eyre!("Configuration file '{dst_name}' already exists"))
+            .suggestion("Please delete the file or use '--force' to overwrite it");
+    }
+
+    let scan = scan(&dir).wrap_err(format!("Failed to scan directory '{dir_name}'"))?;
+    if scan.sources.is_empty() {
+        return Err(eyre!("No C-family sources found in '{dir_name}'")).suggestion(format!(
+            "Please make sure that the directory contains files with any of the extensions {}",
+            SOURCE_EXTENSIONS.join(", ")
+        ));
+    }
+
+    let config = propose(&scan);
+    let content = serde_json::to_string_pretty(&config)? + "\n";
+
+    fs::write(&dst, content)
+        .wrap_err(format!("Failed to write '{dst_name}'"))
+        .suggestion(format!(
+            "Please check the permissions for the folder {dir_name}"
+        ))?;
+
+    log::info!(
+        "Found {} sources and {} style files, created {}",
+        scan.sources.len(),
+        scan.styles.len(),
+        console::style(&dst_name).bold()
+    );
+    if scan.styles.is_empty() {
+        log::warn!(
+            "No style file found, please add a .clang-format file or the fields \
+             'styleFile' and 'styleRoot' to {dst_name}"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_propose() {
+        let scan = Scan {
+            sources: vec![
+                "main.c".into(),
+                "lib/a/a.cpp".into(),
+                "lib/a/a.hpp".into(),
+                "lib/b.h".into(),
+                "src/main.c".into(),
+            ],
+            styles: vec!["style/.clang-format".into(), "style/a/.clang-format".into()],
+        };
+
+        let config = propose(&scan);
+        assert_eq!(
+            vec!["*.c", "lib/**/*.{cpp,h,hpp}", "src/**/*.c"],
+            config.paths
+        );
+        assert_eq!(Some("style/.clang-format".to_string()), config.style_file);
+        assert_eq!(Some(".".to_string()), config.style_root);
+
+        // all fields of the created configuration must be defined by the schema
+        let fields = cli::JsonModel::field_names();
+        let value = serde_json::to_value(&config).unwrap();
+        for field in value.as_object().unwrap().keys() {
+            assert!(fields.contains(field), "unknown field '{field}'");
+        }
+
+        // style files in the root directory are used by clang-format without configuration
+        let styles = vec![".clang-format".into(), "style/.clang-format".into()];
+        assert_eq!(None, propose_style(&styles));
+    }
+}
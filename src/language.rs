@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, path};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+/// Supported languages and the file extensions used by `clang-format` to detect them.
+const LANGUAGES: [(&str, &str); 19] = [
+    ("c", "c"),
+    ("cpp", "cpp"),
+    ("c++", "cpp"),
+    ("cuda", "cu"),
+    ("objc", "m"),
+    ("objcpp", "mm"),
+    ("objc++", "mm"),
+    ("cs", "cs"),
+    ("csharp", "cs"),
+    ("java", "java"),
+    ("js", "js"),
+    ("javascript", "js"),
+    ("ts", "ts"),
+    ("typescript", "ts"),
+    ("json", "json"),
+    ("proto", "proto"),
+    ("textproto", "textpb"),
+    ("tablegen", "td"),
+    ("verilog", "sv"),
+];
+
+fn extension_for(language: &str) -> Option<&'static str> {
+    let language = language.to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, ext)| *ext)
+}
+
+/// Maps files to a language by means of globs.
+///
+/// `clang-format` determines the language using the file extension. Files that match any of the
+/// globs of this map are formatted via stdin using the `--assume-filename` parameter, which
+/// keeps the directory (and thus the lookup of the `.clang-format` file) but appends an extension
+/// that is known to `clang-format`.
+///
+/// Globs without a path separator, e.g., `*.ipp`, match the file name. All other globs match the
+/// path relative to the `root` directory of the configuration file, e.g., `src/gen/*.inc`, where
+/// `*` does not match the path separator.
+pub struct LanguageMap {
+    root: path::PathBuf,
+    entries: Vec<(globset::GlobMatcher, bool, &'static str)>,
+}
+
+impl LanguageMap {
+    pub fn build<P>(
+        map: &Option<BTreeMap<String, String>>,
+        root: P,
+        file: &str,
+    ) -> eyre::Result<Self>
+    where
+        P: AsRef<path::Path>,
+    {
+        let root = root.as_ref().to_path_buf();
+        let map = match map {
+            None => {
+                return Ok(LanguageMap {
+                    root,
+                    entries: vec![],
+                })
+            }
+            Some(map) => map,
+        };
+
+        let mut entries = vec![];
+        for (glob, language) in map.iter() {
+            let ext = extension_for(language)
+                .ok_or(eyre!("Unknown language '{language}' for '{glob}'"))
+                .wrap_err("Error while parsing 'languageMap'")
+                .suggestion(format!(
+                    "Please use one of the supported languages: {}",
+                    LANGUAGES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))?;
+
+            let matcher = globset::GlobBuilder::new(glob)
+                .case_insensitive(cfg!(windows))
+                .literal_separator(true)
+                .build()
+                .map_err(|err| eyre!(err))
+                .wrap_err("Error while parsing 'languageMap'")
+                .suggestion(format!(
+                    "Check the format of the field 'languageMap' in the provided file '{file}'."
+                ))?
+                .compile_matcher();
+
+            entries.push((matcher, glob.contains('/'), ext));
+        }
+        Ok(LanguageMap { root, entries })
+    }
+
+    /// Provides the filename that should be passed to `--assume-filename` for the given path,
+    /// or `None` if `clang-format` can format the file without any mapping.
+    pub fn assume_filename<P>(&self, path: P) -> Option<path::PathBuf>
+    where
+        P: AsRef<path::Path>,
+    {
+        let path = path.as_ref();
+        let name = path.file_name()?;
+        let relative = match path.is_absolute() {
+            true => crate::cli::utils::relative_to(&self.root, path),
+            false => path.to_path_buf(),
+        };
+
+        let (_, _, ext) = self
+            .entries
+            .iter()
+            .find(|(glob, has_dir, _)| match has_dir {
+                true => glob.is_match(&relative),
+                false => glob.is_match(name),
+            })?;
+        let mut name = name.to_os_string();
+        name.push(".");
+        name.push(ext);
+        Some(path.with_file_name(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assume_filename() {
+        let map = Some(BTreeMap::from([
+            ("*.ipp".to_string(), "cpp".to_string()),
+            ("*.proto.in".to_string(), "Proto".to_string()),
+        ]));
+        let map = LanguageMap::build(&map, "/some", "test.json").unwrap();
+
+        assert_eq!(
+            Some(path::PathBuf::from("/some/path/file.ipp.cpp")),
+            map.assume_filename("/some/path/file.ipp")
+        );
+        assert_eq!(
+            Some(path::PathBuf::from("some/file.proto.in.proto")),
+            map.assume_filename("some/file.proto.in")
+        );
+        assert_eq!(None, map.assume_filename("/some/path/file.c"));
+
+        // globs containing a directory match the path relative to the root
+        let map = Some(BTreeMap::from([
+            ("src/gen/*.inc".to_string(), "c".to_string()),
+            ("../other/**/*.inc".to_string(), "cpp".to_string()),
+        ]));
+        let map = LanguageMap::build(&map, "/some/project", "test.json").unwrap();
+        assert_eq!(
+            Some(path::PathBuf::from("/some/project/src/gen/table.inc.c")),
+            map.assume_filename("/some/project/src/gen/table.inc")
+        );
+        assert_eq!(
+            None,
+            map.assume_filename("/some/project/src/gen/sub/table.inc")
+        );
+        assert_eq!(None, map.assume_filename("/some/project/src/table.inc"));
+        assert_eq!(
+            Some(path::PathBuf::from("/some/other/a/b.inc.cpp")),
+            map.assume_filename("/some/other/a/b.inc")
+        );
+
+        let map = Some(BTreeMap::from([("*.x".to_string(), "cobol".to_string())]));
+        assert!(LanguageMap::build(&map, "/some", "test.json").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs, path};
+use std::{collections::BTreeMap, fs, path};
 
 #[allow(unused_imports)]
 use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
@@ -8,8 +8,21 @@ use serde::Deserialize;
 pub mod cli;
 pub mod cmd;
 
+mod backup;
+mod doctor;
+mod explain;
 mod globs;
+mod init;
+mod language;
+mod list;
+mod patch;
 mod resolve;
+mod stdin;
+mod style;
+mod validate;
+mod watch;
+
+pub use style::StyleSource;
 
 // TODO: UTF-8 restriction?
 #[derive(Deserialize, Debug)]
@@ -49,18 +62,56 @@ impl LogStep {
     }
 }
 
-fn get_command(data: &cli::Data) -> eyre::Result<cmd::Runner> {
+/// Resolves the style that clang-format uses for `file` with the configuration `data`, see
+/// [`cmd::Runner::format_buffer`]. The file does not need to exist.
+pub fn style_source<P>(data: &cli::Data, file: P) -> eyre::Result<StyleSource>
+where
+    P: AsRef<path::Path>,
+{
+    let style_and_root = resolve::style_and_root(data)?;
+
+    // style files are looked up in the parent directories of the canonical path
+    let file = file.as_ref();
+    let file = globs::canonical_parent(file).unwrap_or_else(|| file.to_path_buf());
+    Ok(StyleSource::resolve(
+        &file,
+        &style_and_root,
+        &resolve::args(data),
+    ))
+}
+
+/// Provides the validated clang-format command for the configuration `data`.
+pub fn get_command(data: &cli::Data) -> eyre::Result<cmd::Runner> {
     let cmd_path = resolve::command(data)?;
     let mut cmd = cmd::Runner::new(&cmd_path);
 
+    let launcher = resolve::launcher(data)?;
+    let cmd_str = if launcher.is_empty() {
+        cmd_path.to_string_lossy().to_string()
+    } else {
+        format!("{} {}", launcher.join(" "), cmd_path.to_string_lossy())
+    };
+    cmd.set_launcher(launcher);
+    cmd.set_atomic_write(data.atomic_write);
+
+    let from_cli = data.args.is_some();
+    cmd.set_args(resolve::args(data))
+        .wrap_err("Invalid arguments for clang-format")
+        .suggestion(if from_cli {
+            "Please check the arguments passed after '--'".to_string()
+        } else {
+            format!(
+                "Please check the field 'extraArgs' in the provided file '{}'",
+                data.json.name
+            )
+        })?;
+
     cmd.validate()
         .wrap_err(format!(
-            "Failed to execute the specified command '{}'",
-            cmd_path.to_string_lossy()
+            "Failed to execute the specified command '{cmd_str}'"
         ))
         .suggestion(format!(
-            "Please make sure that the command '{}' exists or is in your search path",
-            cmd_path.to_string_lossy()
+            "Please make sure that the command '{cmd_str}' exists or is in your search path"
         ))?;
 
     Ok(cmd)
@@ -156,13 +207,66 @@ fn setup_jobs(jobs: Option<u8>) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Resolves the set of files selected by the configuration.
+fn select_paths(data: &cli::Data) -> eyre::Result<globs::Selection> {
+    select_paths_with(data, &resolve::walk_options(data))
+}
+
+/// Resolves the set of files selected by the configuration, using the given walk `options`.
+fn select_paths_with(
+    data: &cli::Data,
+    options: &globs::WalkOptions,
+) -> eyre::Result<globs::Selection> {
+    let candidates =
+        globs::build_matchers_from(&data.json.paths, &data.json.root, "paths", &data.json.name)?;
+    let filter_pre =
+        globs::build_glob_set_from(&data.json.filter_pre, "preFilter", &data.json.name)?;
+    let filter_post =
+        globs::build_glob_set_from(&data.json.filter_post, "postFilter", &data.json.name)?;
+
+    globs::match_paths(
+        candidates,
+        filter_pre,
+        filter_post,
+        &data.json.extensions,
+        options,
+    )
+}
+
 pub fn run(data: cli::Data) -> eyre::Result<()> {
+    if let Some(file) = &data.stdin_filename {
+        return stdin::run(&data, file);
+    }
+    match &data.cmd {
+        cli::Command::List(options) => return list::run(&data, *options),
+        cli::Command::Explain(path) => return explain::run(&data, path),
+        cli::Command::Validate { strict } => return validate::run(&data, *strict),
+        cli::Command::Doctor => return doctor::run(&data),
+        cli::Command::Restore {
+            run_id,
+            list,
+            force,
+        } => return backup::restore(&data, run_id, *list, *force),
+        cli::Command::Watch { check, interval } => {
+            let (check, interval) = (*check, *interval);
+            return watch::run(data, check, interval);
+        }
+        _ => (),
+    }
+
+    setup_jobs(data.jobs)?;
+    execute(&data, None)
+}
+
+/// Formats or checks the files selected by the configuration `data`, or only the given `paths`
+/// if provided. The paths must be canonical.
+fn execute(data: &cli::Data, paths: Option<Vec<path::PathBuf>>) -> eyre::Result<()> {
     let start = std::time::Instant::now();
 
     log::info!(" ");
     let mut step = LogStep::new();
 
-    let style_and_root = resolve::style_and_root(&data)?;
+    let style_and_root = resolve::style_and_root(data)?;
     if let Some((style_file, _)) = &style_and_root {
         log::info!(
             "{} Found style file {}",
@@ -181,50 +285,82 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
                  the file paths cannot be checked.",
             );
         }
+        if data.strict_style {
+            return Err(eyre::eyre!("Missing style root for strict style check")).suggestion(
+                "The strict style check is only available when specifying a 'styleRoot' \
+                 directory, since the placed style file can only be shadowed within this \
+                 directory.",
+            );
+        }
     }
 
-    let candidates =
-        globs::build_matchers_from(&data.json.paths, &data.json.root, "paths", &data.json.name)?;
-    let filter_pre =
-        globs::build_glob_set_from(&data.json.filter_pre, "preFilter", &data.json.name)?;
-    let filter_post =
-        globs::build_glob_set_from(&data.json.filter_post, "postFilter", &data.json.name)?;
+    let languages =
+        language::LanguageMap::build(&data.json.language_map, &data.json.root, &data.json.name)?;
+    let paths = match paths {
+        Some(paths) => {
+            log::info!(
+                "{} Processing {} of the selected files",
+                step.next(),
+                console::style(paths.len()).bold(),
+            );
+            paths
+        }
+        None => {
+            let selection = select_paths(data)?;
+
+            if data.warn_overlaps {
+                for (path, globs) in selection.overlaps.iter() {
+                    log::warn!(
+                        "{} is matched by multiple entries in 'paths': {}",
+                        path.to_string_lossy(),
+                        globs.join(", ")
+                    );
+                }
+            }
 
-    let (paths, filtered) = globs::match_paths(candidates, filter_pre, filter_post);
-    let paths = paths.into_iter().map(|p| p.canonicalize().unwrap());
+            let filtered = selection.filtered;
+            let filtered = if filtered.is_empty() {
+                "".to_string()
+            } else {
+                format!(" (filtered {} paths)", filtered.len())
+            };
 
-    let filtered = if filtered.is_empty() {
-        "".to_string()
-    } else {
-        format!(" (filtered {} paths)", filtered.len())
+            log::info!(
+                "{} Found {} files for the provided path patterns{}",
+                step.next(),
+                console::style(selection.paths.len()).bold(),
+                filtered
+            );
+            selection.paths
+        }
     };
 
-    log::info!(
-        "{} Found {} files for the provided path patterns{}",
-        step.next(),
-        console::style(paths.len()).bold(),
-        filtered
-    );
-
     // log::info!(
     //     "{}",
     //     serde_json::to_string_pretty(&paths.clone().collect::<Vec<_>>()).unwrap()
     // );
 
-    let cmd = get_command(&data)?;
+    let cmd = get_command(data)?;
     let cmd_path = match cmd.get_path().canonicalize() {
         Ok(path) => path,
         Err(_) => cmd.get_path(),
     };
+    let launcher = if cmd.get_launcher().is_empty() {
+        "".to_string()
+    } else {
+        format!(
+            " via {}",
+            console::style(cmd.get_launcher().join(" ")).bold()
+        )
+    };
     log::info!(
-        "{} Found clang-format version {} using command {}",
+        "{} Found clang-format version {} using command {}{}",
         step.next(),
         console::style(cmd.get_version().unwrap()).bold(),
         console::style(cmd_path.to_string_lossy()).bold(),
+        launcher
     );
 
-    let paths: Vec<_> = paths.collect();
-
     // strict root directory check: all files must be siblings of the style root.
     if data.strict_root {
         // we're checking that --style-root is only allowed if a styleRoot
@@ -265,6 +401,48 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         }
     }
 
+    // existing style files within the style root take precedence over the placed style file,
+    // such that the affected files are formatted using a different style.
+    let shadowing = style::shadowing(paths.iter(), &style_and_root);
+    for (style, shadowed) in shadowing.iter() {
+        log::warn!(
+            "The style file {} takes precedence over the placed style file for {} files",
+            style.to_string_lossy(),
+            shadowed.len()
+        );
+        log::debug!(
+            "Files formatted using {}:\n{}",
+            style.to_string_lossy(),
+            serde_json::to_string_pretty(&shadowed).unwrap()
+        );
+    }
+    if !shadowing.is_empty() && (data.strict_root || data.strict_style) {
+        return Err(eyre::eyre!(
+            "Found {} style files within the 'styleRoot' directory that shadow the placed style file",
+            shadowing.len()
+        ))
+        .suggestion(
+            "Please delete the listed style files or exclude the affected files from 'paths'. \
+             Use -vv to list the affected files.",
+        );
+    }
+
+    // report the style that clang-format uses, grouped by source
+    let args = resolve::args(data);
+    let mut sources: BTreeMap<String, usize> = BTreeMap::new();
+    for path in paths.iter() {
+        let source = style::StyleSource::resolve(path, &style_and_root, &args);
+        log::debug!("{} uses style {}", path.to_string_lossy(), source);
+        *sources.entry(source.to_string()).or_default() += 1;
+    }
+    for (source, count) in sources {
+        log::info!(
+            "Using style {} for {} files",
+            console::style(source).bold(),
+            count
+        );
+    }
+
     let strip_root = if let Some((_, style_root)) = &style_and_root {
         Some(path::PathBuf::from(style_root.as_path()))
     } else {
@@ -284,7 +462,6 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         }
     });
 
-    setup_jobs(data.jobs)?;
     log::info!("{} Executing clang-format ...\n", step.next(),);
 
     let pb = indicatif::ProgressBar::new(paths.len() as u64);
@@ -302,39 +479,237 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         pb.set_prefix("Running");
     }
 
+    // files are only modified in place when formatting or fixing
+    let modifies = match data.cmd {
+        cli::Command::Format => {
+            data.emit_patch.is_none() && data.output_dir.is_none() && !data.verify_idempotent
+        }
+        cli::Command::Check { fix } => fix,
+        _ => false,
+    };
+    let backup = (modifies && data.backup).then(|| backup::Backup::new(&data.json.root));
+
+    // summary of the execution, and the changed files if requested
+    let mut summary = None;
+    let mut list_changed = None;
     let result: eyre::Result<()> = match data.cmd {
-        cli::Command::Format => paths.into_par_iter().try_for_each(|path| {
-            // TODO: instead of strip_root, try to find a common path component
-            // and if it is shorter than the absolute path, use that.
-            // TODO: if there was a path outside of .clang-format it won't work anyhow ?
-            log_step(
-                "Formatting",
-                path.as_path(),
-                &strip_root,
-                &pb,
-                console::Style::new().green().bold(),
-            );
+        cli::Command::Format if data.verify_idempotent => {
+            let total = paths.len();
+            let diffs: eyre::Result<Vec<_>> = paths
+                .into_par_iter()
+                .map(|path| {
+                    let diff = patch::idempotence_diff(&cmd, &languages, &data.json.root, &path)?;
+                    let (prefix, style) = match diff {
+                        Some(_) => ("Unstable", console::Style::new().red().bold()),
+                        None => ("Stable", console::Style::new().green().bold()),
+                    };
+                    log_step(prefix, path.as_path(), &strip_root, &pb, style);
+                    Ok(diff.map(|diff| (path, diff)))
+                })
+                .collect();
 
-            cmd.run_format(&path)
-                .wrap_err(format!("Failed to format {}", path.to_string_lossy()))
+            let unstable: Vec<_> = diffs?.into_iter().flatten().collect();
+            if unstable.is_empty() {
+                summary = Some(format!("Formatting is idempotent for all {total} files"));
+                Ok(())
+            } else {
+                for (path, diff) in unstable.iter() {
+                    log::warn!(
+                        "Formatting {} again changes the formatted content:\n{}",
+                        path.to_string_lossy(),
+                        diff
+                    );
+                }
+                Err(eyre::eyre!(format!(
+                    "Formatting is not idempotent for {} of {} files:\n{}\n ",
+                    unstable.len(),
+                    total,
+                    unstable
+                        .iter()
+                        .map(|(path, _)| format!("{}", path.to_string_lossy()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )))
                 .suggestion(
-                    "Please make sure that your style file matches \
-                    the version of clang-format and that you have the \
-                    necessary permissions to modify all files",
-                )?;
+                    "This is typically caused by a bug in clang-format. Please try to change \
+                     the style options affecting the reported changes, or a different version \
+                     of clang-format",
+                )
+            }
+        }
+        cli::Command::Format if data.emit_patch.is_some() => {
+            let total = paths.len();
+            let cwd = std::env::current_dir()
+                .and_then(|cwd| cwd.canonicalize())
+                .wrap_err("Failed to determine the working directory")?;
+
+            // paths in the patch are relative to the working directory, 'git apply' rejects
+            // paths with '..' components
+            let outside: Vec<_> = paths.iter().filter(|p| !p.starts_with(&cwd)).collect();
+            if !outside.is_empty() {
+                return Err(eyre::eyre!(
+                    "Found {} files outside the working directory {}:\n{}",
+                    outside.len(),
+                    cwd.to_string_lossy(),
+                    outside
+                        .iter()
+                        .map(|p| format!("{}", p.to_string_lossy()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ))
+                .suggestion(
+                    "The option --emit-patch creates paths relative to the working directory. \
+                     Please execute the tool from a common parent directory of all files, e.g., \
+                     the root of the repository.",
+                );
+            }
+
+            let diffs: eyre::Result<Vec<_>> = paths
+                .into_par_iter()
+                .map(|path| {
+                    let diff = patch::diff_file(&cmd, &languages, &cwd, &path)?;
+                    let (prefix, style) = match diff {
+                        Some(_) => ("Changed", console::Style::new().green().bold()),
+                        None => ("Unchanged", console::Style::new().dim()),
+                    };
+                    log_step(prefix, path.as_path(), &strip_root, &pb, style);
+                    Ok(diff)
+                })
+                .collect();
+
+            let diffs: Vec<_> = diffs?.into_iter().flatten().collect();
+            let patch = data.emit_patch.as_ref().unwrap();
+            if diffs.is_empty() {
+                // 'git apply' rejects empty patches, a stale patch of a previous run is removed
+                if patch.is_file() {
+                    fs::remove_file(patch).wrap_err(format!(
+                        "Failed to remove the existing patch file {}",
+                        patch.to_string_lossy()
+                    ))?;
+                }
+                summary = Some(format!("All {total} files are formatted, no patch created"));
+            } else {
+                fs::write(patch, diffs.concat()).wrap_err(format!(
+                    "Failed to write the patch file {}",
+                    patch.to_string_lossy()
+                ))?;
+                summary = Some(format!(
+                    "Created patch {} for {} of {} files",
+                    patch.to_string_lossy(),
+                    diffs.len(),
+                    total
+                ));
+            }
             Ok(())
-        }),
-        cli::Command::Check => {
+        }
+        cli::Command::Format if data.output_dir.is_some() => {
+            let output_dir = data.output_dir.as_ref().unwrap();
+            let root = &data.json.root;
+
+            // the mirrored tree is relative to the configuration file, other files have no
+            // location within the output directory
+            let outside: Vec<_> = paths.iter().filter(|p| !p.starts_with(root)).collect();
+            if !outside.is_empty() {
+                return Err(eyre::eyre!(
+                    "Found {} files outside the directory of the configuration file {}:\n{}",
+                    outside.len(),
+                    data.json.name,
+                    outside
+                        .iter()
+                        .map(|p| format!("{}", p.to_string_lossy()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ))
+                .suggestion(
+                    "The option --output-dir mirrors the files relative to the directory of the \
+                     configuration file. Please move the configuration file to a common parent \
+                     directory of all files.",
+                );
+            }
+
+            let total = paths.len();
+            let written: eyre::Result<Vec<_>> = paths
+                .into_par_iter()
+                .map(|path| {
+                    let (content, formatted) = format_to_vec(&cmd, &languages, &path)?;
+                    let dst = output_dir.join(path.strip_prefix(root).unwrap());
+                    let dst_name = dst.to_string_lossy();
+                    if let Some(parent) = dst.parent() {
+                        fs::create_dir_all(parent)
+                            .wrap_err(format!("Failed to create directory for {dst_name}"))?;
+                    }
+                    fs::write(&dst, &formatted).wrap_err(format!("Failed to write {dst_name}"))?;
+
+                    let changed = content != formatted;
+                    let (prefix, style) = match changed {
+                        true => ("Reformatted", console::Style::new().green().bold()),
+                        false => ("Copied", console::Style::new().dim()),
+                    };
+                    log_step(prefix, path.as_path(), &strip_root, &pb, style);
+                    Ok(changed)
+                })
+                .collect();
+
+            let changed = written?.into_iter().filter(|changed| *changed).count();
+            summary = Some(format!(
+                "Reformatted {} of {} files into {}",
+                changed,
+                total,
+                output_dir.to_string_lossy()
+            ));
+            Ok(())
+        }
+        cli::Command::Format => {
+            let total = paths.len();
+            let formatted: eyre::Result<Vec<_>> = paths
+                .into_par_iter()
+                .map(|path| {
+                    // TODO: instead of strip_root, try to find a common path component
+                    // and if it is shorter than the absolute path, use that.
+                    // TODO: if there was a path outside of .clang-format it won't work anyhow ?
+                    let changed = format_file(&cmd, &languages, backup.as_ref(), &path)?;
+                    let (prefix, style) = match changed {
+                        true => ("Reformatted", console::Style::new().green().bold()),
+                        false => ("Unchanged", console::Style::new().dim()),
+                    };
+                    log_step(prefix, path.as_path(), &strip_root, &pb, style);
+                    Ok((path, changed))
+                })
+                .collect();
+
+            let changed: Vec<_> = formatted?
+                .into_iter()
+                .filter_map(|(path, changed)| changed.then_some(path))
+                .collect();
+            summary = Some(format!("Reformatted {} of {} files", changed.len(), total));
+            if data.list_changed {
+                list_changed = Some(changed);
+            }
+            Ok(())
+        }
+        cli::Command::List(_)
+        | cli::Command::Explain(_)
+        | cli::Command::Validate { .. }
+        | cli::Command::Doctor
+        | cli::Command::Restore { .. }
+        | cli::Command::Watch { .. } => {
+            unreachable!("subcommands are handled separately")
+        }
+        cli::Command::Check { fix } => {
             if let Err(err) = cmd.supports_check_or_err() {
                 return Err(err).wrap_err("Check mode is not supported").suggestion(
                     "Please use a version of clang-format that supports the --dry-run option",
                 );
             }
 
-            let failures: Vec<_> = paths
+            let failures: Vec<(path::PathBuf, path::PathBuf, String)> = paths
                 .into_par_iter()
                 .map(|path| {
-                    let result = match cmd.run_check(&path) {
+                    let result = match languages.assume_filename(&path) {
+                        None => cmd.run_check(&path),
+                        Some(assume) => cmd.run_check_as(&path, assume),
+                    };
+                    let result = match result {
                         Ok(_) => None,
                         Err(err) => {
                             let print_path = match &strip_root {
@@ -347,7 +722,7 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
                                     }
                                 }
                             };
-                            Some((print_path, format!("{err}")))
+                            Some((path.clone(), print_path, format!("{err}")))
                         }
                     };
                     let (prefix, style) = match result {
@@ -357,7 +732,7 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
                     log_step(prefix, path.as_path(), &strip_root, &pb, style);
                     if let Some(err) = &result {
                         if !log_pretty() {
-                            log::error!("{}", err.1);
+                            log::error!("{}", err.2);
                         }
                     }
                     result
@@ -365,20 +740,57 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
                 .flatten()
                 .collect();
 
-            if !failures.is_empty() {
+            if failures.is_empty() {
+                Ok(())
+            } else if !fix {
                 Err(eyre::eyre!(format!(
                     "Format check failed for the following files:\n{}\n ",
                     failures
                         .into_iter()
-                        .map(|result| format!("{}", result.0.to_string_lossy()))
+                        .map(|result| format!("{}", result.1.to_string_lossy()))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )))
             } else {
-                Ok(())
+                // only the mismatching files are formatted, all other files remain untouched
+                pb.inc_length(failures.len() as u64);
+                let fixed: eyre::Result<Vec<_>> = failures
+                    .into_par_iter()
+                    .map(|(path, print_path, _)| {
+                        format_file(&cmd, &languages, backup.as_ref(), &path)?;
+                        log_step(
+                            "Fixed",
+                            path.as_path(),
+                            &strip_root,
+                            &pb,
+                            console::Style::new().yellow().bold(),
+                        );
+                        Ok(print_path)
+                    })
+                    .collect();
+                let fixed = fixed?;
+                Err(eyre::eyre!(format!(
+                    "Fixed the format of the following files:\n{}\n ",
+                    fixed
+                        .iter()
+                        .map(|path| format!("{}", path.to_string_lossy()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )))
+                .suggestion("Please review and commit the fixed files")
             }
         }
     };
+    // the backup is also kept if the execution failed, since files may have been changed
+    if let Some(backup) = backup {
+        if let Some((run_id, count)) = backup.finish()? {
+            log::info!(
+                "Created backup {} of {} files, use the subcommand 'restore' to revert the changes",
+                console::style(run_id).bold(),
+                count
+            );
+        }
+    }
     result?;
 
     let duration = start.elapsed();
@@ -393,11 +805,94 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
     } else {
         log::info!("{} Finished in {:#?}", step.next(), duration);
     }
+    if let Some(summary) = summary {
+        log::info!("{}", summary);
+    }
+    if let Some(changed) = list_changed {
+        print_changed(&data.json.root, &changed)
+            .wrap_err("Failed to print the list of changed files")?;
+    }
 
     // log::info!(" "); // just an empty newline
     Ok(())
 }
 
+/// Formats the file at `path`, providing whether clang-format changed its content. The original
+/// content of changed files is saved to the `backup`, if provided.
+fn format_file(
+    cmd: &cmd::Runner,
+    languages: &language::LanguageMap,
+    backup: Option<&backup::Backup>,
+    path: &path::Path,
+) -> eyre::Result<bool> {
+    let before = fs::read(path).wrap_err(format!("Failed to read {}", path.to_string_lossy()))?;
+
+    let result = match languages.assume_filename(path) {
+        None => cmd.run_format(path),
+        Some(assume) => cmd.run_format_as(path, assume),
+    }
+    .wrap_err(format!("Failed to format {}", path.to_string_lossy()))
+    .suggestion(
+        "Please make sure that your style file matches \
+            the version of clang-format and that you have the \
+            necessary permissions to modify all files",
+    )
+    .and_then(|_| fs::read(path).wrap_err(format!("Failed to read {}", path.to_string_lossy())));
+
+    // the file may have been modified even if clang-format failed
+    let (after, err) = match result {
+        Ok(after) => (Some(after), None),
+        Err(err) => (fs::read(path).ok(), Some(err)),
+    };
+    let changed = after.as_ref().is_some_and(|after| *after != before);
+    if let (Some(backup), true, Some(after)) = (backup, changed, &after) {
+        backup.save(path, &before, after)?;
+    }
+    match err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+/// Formats the file at `path` via stdin without modifying it, providing the file's content and
+/// the formatted content.
+fn format_to_vec(
+    cmd: &cmd::Runner,
+    languages: &language::LanguageMap,
+    path: &path::Path,
+) -> eyre::Result<(Vec<u8>, Vec<u8>)> {
+    let content = fs::read(path).wrap_err(format!("Failed to read {}", path.to_string_lossy()))?;
+    let assume = languages
+        .assume_filename(path)
+        .unwrap_or_else(|| path.to_path_buf());
+
+    let formatted = cmd
+        .format_stdin(&content, &assume)
+        .wrap_err(format!("Failed to format {}", path.to_string_lossy()))
+        .suggestion("Please make sure that your style file matches the version of clang-format")?;
+    Ok((content, formatted))
+}
+
+/// Prints the `changed` files relative to the configuration file `root`, one path per line.
+fn print_changed(root: &path::Path, changed: &[path::PathBuf]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut out = std::io::stdout().lock();
+    for path in changed {
+        let result = writeln!(
+            out,
+            "{}",
+            cli::utils::relative_to(root, path).to_string_lossy()
+        );
+        match result {
+            // the output is typically piped into other tools, which may exit early
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
 fn log_step(
     prefix: &str,
     path: &path::Path,
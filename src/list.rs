@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::Serialize;
+
+use crate::{
+    cli::{self, utils::relative_to},
+    globs, language, resolve, style,
+};
+
+#[derive(Serialize, Debug)]
+struct FileEntry {
+    path: path::PathBuf,
+    style: style::StyleSource,
+    /// Whether `clang-format --dump-config` confirms the style file of the style source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirmed: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+struct FilteredEntry {
+    path: path::PathBuf,
+    reason: globs::Filter,
+}
+
+#[derive(Serialize, Debug)]
+struct Listing {
+    root: path::PathBuf,
+    files: Vec<FileEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filtered: Option<Vec<FilteredEntry>>,
+}
+
+/// Prints the resolved file set without executing `clang-format`.
+pub fn run(data: &cli::Data, options: cli::ListOptions) -> eyre::Result<()> {
+    let style_and_root = resolve::style_and_root(data)?;
+    let selection = crate::select_paths(data)?;
+
+    let root = &data.json.root;
+    let args = resolve::args(data);
+    let sources: Vec<_> = selection
+        .paths
+        .iter()
+        .map(|p| style::StyleSource::resolve(p, &style_and_root, &args))
+        .collect();
+
+    // the style sources are only confirmed for the JSON output, such that the plain listing
+    // never executes clang-format
+    let confirmed = match options.json {
+        false => vec![None; sources.len()],
+        true => confirm(data, &selection.paths, &sources).unwrap_or_else(|err| {
+            log::warn!("Failed to confirm the style sources: {err:?}");
+            vec![None; sources.len()]
+        }),
+    };
+
+    let files: Vec<_> = selection
+        .paths
+        .iter()
+        .zip(sources)
+        .zip(confirmed)
+        .map(|((p, source), confirmed)| FileEntry {
+            path: relative_to(root, p),
+            style: source.map_paths(|p| relative_to(root, p)),
+            confirmed,
+        })
+        .collect();
+    let filtered: Vec<_> = selection
+        .filtered
+        .iter()
+        .map(|(p, reason)| FilteredEntry {
+            path: relative_to(root, p),
+            reason: *reason,
+        })
+        .collect();
+
+    let listing = Listing {
+        root: root.clone(),
+        files,
+        filtered: options.show_filtered.then_some(filtered),
+    };
+
+    match print(&listing, options.json) {
+        // the output is typically piped into other tools, which may exit early (e.g., `head`)
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.wrap_err("Failed to print the list of files"),
+    }
+}
+
+/// Confirms the style `sources` of the `paths` using `clang-format --dump-config`, which is the
+/// ground truth for the style that clang-format uses: The configuration reported for a file must
+/// match the configuration of its style file evaluated in isolation. This is not the case, e.g.,
+/// if the style file uses `InheritParentConfig` or if clang-format loads a different file.
+/// Sources without a style file are not confirmed.
+fn confirm(
+    data: &cli::Data,
+    paths: &[path::PathBuf],
+    sources: &[style::StyleSource],
+) -> eyre::Result<Vec<Option<bool>>> {
+    let cmd = crate::get_command(data)?;
+    let languages =
+        language::LanguageMap::build(&data.json.language_map, &data.json.root, &data.json.name)?;
+
+    // the configured style file is placed temporarily, just like when formatting
+    let placed = sources.iter().find_map(|source| match source {
+        style::StyleSource::Placed {
+            style_file,
+            placed_as,
+        } if !placed_as.exists() => Some((style_file, placed_as)),
+        _ => None,
+    });
+    let placed = match placed {
+        None => None,
+        Some((style_file, placed_as)) => {
+            fs::copy(style_file, placed_as).wrap_err(format!(
+                "Failed to copy style file to {}",
+                placed_as.to_string_lossy()
+            ))?;
+            Some(placed_as.clone())
+        }
+    };
+    let _placed = scopeguard::guard(placed, |path| {
+        if let Some(path) = path {
+            let _ = fs::remove_file(path);
+        }
+    });
+
+    // the configuration only depends on the directory and the language of a file
+    let mut dumped = HashMap::new();
+    let mut isolated = HashMap::new();
+    let mut confirmed = vec![];
+    for (path, source) in paths.iter().zip(sources) {
+        let Some(style_file) = source.style_file() else {
+            confirmed.push(None);
+            continue;
+        };
+        let assume = languages
+            .assume_filename(path)
+            .unwrap_or_else(|| path.clone());
+        let ext = assume
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let key = (assume.parent().map(path::PathBuf::from), ext.clone());
+        if !dumped.contains_key(&key) {
+            dumped.insert(key.clone(), cmd.dump_config(&assume).ok());
+        }
+        let key_isolated = (style_file.to_path_buf(), ext.clone());
+        if !isolated.contains_key(&key_isolated) {
+            let dump = style::dump_isolated(&cmd, style_file, &ext).ok();
+            isolated.insert(key_isolated.clone(), dump);
+        }
+
+        let is_confirmed = match (&dumped[&key], &isolated[&key_isolated]) {
+            (Some(dumped), Some(isolated)) => dumped == isolated,
+            _ => false,
+        };
+        if !is_confirmed {
+            log::warn!(
+                "clang-format does not use the style file {} for {}",
+                style_file.to_string_lossy(),
+                path.to_string_lossy()
+            );
+        }
+        confirmed.push(Some(is_confirmed));
+    }
+    Ok(confirmed)
+}
+
+fn print(listing: &Listing, json: bool) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    if json {
+        serde_json::to_writer_pretty(&mut out, listing)?;
+        return writeln!(out);
+    }
+
+    for file in listing.files.iter() {
+        writeln!(out, "{}", file.path.to_string_lossy())?;
+    }
+    // filtered paths use the syntax of negated entries in 'paths'
+    for entry in listing.filtered.iter().flatten() {
+        writeln!(
+            out,
+            "!{} ({})",
+            entry.path.to_string_lossy(),
+            entry.reason.as_str()
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::path;
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli::utils::relative_to, cmd, language};
+
+/// Name of `path` within a patch: relative to `root` and using forward slashes on all platforms,
+/// as expected by `git apply`.
+pub fn patch_name(root: &path::Path, path: &path::Path) -> String {
+    relative_to(root, path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Unified diff in the format of `git diff` for the file `name`, or `None` if the contents match.
+pub fn unified_diff(name: &str, original: &[u8], formatted: &[u8]) -> Option<String> {
+    if original == formatted {
+        return None;
+    }
+    let original = String::from_utf8_lossy(original);
+    let formatted = String::from_utf8_lossy(formatted);
+
+    let diff = similar::TextDiff::from_lines(original.as_ref(), formatted.as_ref());
+    let hunks = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{name}"), &format!("b/{name}"))
+        .to_string();
+    Some(format!("diff --git a/{name} b/{name}\n{hunks}"))
+}
+
+/// Formats the file at `path` via stdin without modifying it, and provides the diff of the
+/// formatted content against the file's content.
+pub fn diff_file(
+    cmd: &cmd::Runner,
+    languages: &language::LanguageMap,
+    root: &path::Path,
+    path: &path::Path,
+) -> eyre::Result<Option<String>> {
+    let (content, formatted) = crate::format_to_vec(cmd, languages, path)?;
+    Ok(unified_diff(&patch_name(root, path), &content, &formatted))
+}
+
+/// Formats the file at `path` twice via stdin without modifying it, and provides the diff of the
+/// second pass against the first pass, i.e., `None` if formatting is idempotent.
+pub fn idempotence_diff(
+    cmd: &cmd::Runner,
+    languages: &language::LanguageMap,
+    root: &path::Path,
+    path: &path::Path,
+) -> eyre::Result<Option<String>> {
+    let (_, first) = crate::format_to_vec(cmd, languages, path)?;
+    let assume = languages
+        .assume_filename(path)
+        .unwrap_or_else(|| path.to_path_buf());
+    let second = cmd.format_stdin(&first, &assume).wrap_err(format!(
+        "Failed to format the formatted content of {}",
+        path.to_string_lossy()
+    ))?;
+    Ok(unified_diff(&patch_name(root, path), &first, &second))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(None, unified_diff("a.c", b"int a;\n", b"int a;\n"));
+
+        let diff = unified_diff("src/a.c", b"int a;  \nint b;\n", b"int a;\nint b;\n").unwrap();
+        assert_eq!(
+            "diff --git a/src/a.c b/src/a.c\n\
+             --- a/src/a.c\n\
+             +++ b/src/a.c\n\
+             @@ -1,2 +1,2 @@\n\
+             -int a;  \n\
+             +int a;\n \
+             int b;\n",
+            diff
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::path;
 
-use crate::cli::{self, utils};
+use crate::{
+    cli::{self, utils},
+    globs,
+};
 
 #[allow(unused_imports)]
 use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
@@ -142,6 +145,16 @@ pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
         },
     };
 
+    if from_json && has_launcher(data) {
+        // the command may only exist within the environment of the launcher, e.g., a container,
+        // thus it is not checked on the host but validated by executing it using the launcher
+        return Ok(match cmd.parent() {
+            Some(dir) if cmd.is_relative() && !dir.as_os_str().is_empty() => {
+                data.json.root.join(cmd)
+            }
+            _ => cmd,
+        });
+    }
     if from_json {
         return utils::executable_or_exists(cmd.as_path(), Some(data.json.root.as_path()))
             .wrap_err("Invalid configuration for field 'command'")
@@ -154,6 +167,82 @@ pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
     Ok(cmd)
 }
 
+/// Whether a launcher is specified, either in the configuration file or as parameter.
+pub fn has_launcher(data: &cli::Data) -> bool {
+    data.launcher.is_some() || data.json.command_launcher.is_some()
+}
+
+pub fn launcher(data: &cli::Data) -> eyre::Result<Vec<String>> {
+    let (launcher, root) = match (&data.json.command_launcher, &data.launcher) {
+        (None, None) => return Ok(vec![]),
+        // launcher defined as CLI parameter, overrides any launcher of the configuration file
+        (cfg, Some(launcher_cli)) => {
+            if let Some(launcher_cfg) = cfg {
+                log::debug!(
+                    "Override detected:\nLauncher '{}' \
+                        specified in '{}' is overridden by the command line parameter: '{}'\n",
+                    launcher_cfg.join(" "),
+                    data.json.name,
+                    launcher_cli.join(" ")
+                );
+            }
+            (launcher_cli, None)
+        }
+        // launcher defined in the .json configuration file but not as CLI parameter
+        (Some(launcher_cfg), None) => (launcher_cfg, Some(data.json.root.as_path())),
+    };
+
+    let (exe, args) = launcher
+        .split_first()
+        .ok_or(eyre!("Empty launcher"))
+        .wrap_err("Invalid configuration for field 'commandLauncher'")
+        .suggestion("Please provide at least the launcher executable or remove the field.")?;
+
+    // the launcher executable is resolved just like the 'command', i.e., relative to the <JSON>
+    // root directory for the configuration file and relative to the working directory otherwise
+    let exe = utils::executable_or_exists(path::Path::new(exe), root)
+        .wrap_err(format!("Invalid launcher '{exe}'"))
+        .suggestion(
+            "Please make sure that the launcher is either a valid absolute path, \
+                a valid relative path or a known application",
+        )?;
+
+    let mut launcher = vec![exe.to_string_lossy().to_string()];
+    launcher.extend(args.iter().cloned());
+    Ok(launcher)
+}
+
+pub fn args(data: &cli::Data) -> Vec<String> {
+    match (&data.json.extra_args, &data.args) {
+        (None, None) => vec![],
+        // arguments defined as CLI parameter but not in the .json configuration file
+        (None, Some(args_cli)) => args_cli.clone(),
+        // arguments defined in the .json configuration file but not as CLI parameter
+        (Some(args_cfg), None) => args_cfg.clone(),
+        // arguments defined in both, the .json configuration file and as CLI parameter
+        (Some(args_cfg), Some(args_cli)) => {
+            log::debug!(
+                "Override detected:\nArguments '{}' \
+                    specified in '{}' are overridden by the command line parameter: '{}'\n",
+                args_cfg.join(" "),
+                data.json.name,
+                args_cli.join(" ")
+            );
+            args_cli.clone()
+        }
+    }
+}
+
+pub fn walk_options(data: &cli::Data) -> globs::WalkOptions {
+    globs::WalkOptions {
+        respect_ignore_files: data.json.respect_ignore_files.unwrap_or(false),
+        include_hidden: data.json.include_hidden,
+        follow_symlinks: data.json.follow_symlinks.unwrap_or_default(),
+        broken_symlinks: data.json.broken_symlinks.unwrap_or_default(),
+        only: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -0,0 +1,73 @@
+use std::{
+    io::{self, Read, Write},
+    path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli, globs, language, resolve};
+
+/// Whether the file at `path` is selected by the configuration. Only the directories containing
+/// the file are searched, such that the check is fast also for large trees. The file may not
+/// exist, e.g., for unsaved buffers in an editor, but its parent directory must exist.
+fn is_selected(data: &cli::Data, path: &path::Path) -> eyre::Result<bool> {
+    let path = match path
+        .canonicalize()
+        .ok()
+        .or_else(|| globs::canonical_parent(path))
+    {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    let options = globs::WalkOptions {
+        only: Some(path.clone()),
+        ..resolve::walk_options(data)
+    };
+    let selection = crate::select_paths_with(data, &options)?;
+    Ok(selection.paths.contains(&path))
+}
+
+/// Reads the content from stdin and writes it to stdout, formatted if `file` is selected by the
+/// configuration and unchanged otherwise. The style file is passed to clang-format explicitly,
+/// thus it is not placed in the `styleRoot` directory.
+pub fn run(data: &cli::Data, file: &path::Path) -> eyre::Result<()> {
+    let mut content = vec![];
+    io::stdin()
+        .lock()
+        .read_to_end(&mut content)
+        .wrap_err("Failed to read from stdin")?;
+
+    let output = match is_selected(data, file)? {
+        false => {
+            log::info!(
+                "{} is not selected by the configuration, skipping",
+                file.to_string_lossy()
+            );
+            content
+        }
+        true => {
+            let languages = language::LanguageMap::build(
+                &data.json.language_map,
+                &data.json.root,
+                &data.json.name,
+            )?;
+            let assume = languages
+                .assume_filename(file)
+                .unwrap_or_else(|| file.to_path_buf());
+            let style = crate::style_source(data, file)?;
+
+            crate::get_command(data)?
+                .format_buffer(&content, assume, style)
+                .wrap_err(format!("Failed to format {}", file.to_string_lossy()))
+                .suggestion(
+                    "Please make sure that your style file matches the version of clang-format",
+                )?
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    out.write_all(&output)
+        .and_then(|_| out.flush())
+        .wrap_err("Failed to write to stdout")
+}
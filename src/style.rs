@@ -0,0 +1,268 @@
+use std::{collections::BTreeMap, fmt, fs, path};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::Serialize;
+
+use crate::cmd;
+
+/// Names of the style files that clang-format searches for in each parent directory of a file
+/// when executed with `-style=file`, in the order they are checked.
+pub const STYLE_FILE_NAMES: [&str; 2] = [".clang-format", "_clang-format"];
+
+/// The style file that clang-format picks up for a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleLookup {
+    /// A style file that exists within the parent directories of the file.
+    Existing(path::PathBuf),
+    /// The configured `styleFile`, which is copied into the `styleRoot` directory before
+    /// executing clang-format.
+    Placed {
+        style_file: path::PathBuf,
+        dst: path::PathBuf,
+    },
+}
+
+/// Finds the style file that clang-format uses for `file`, i.e., the first style file in any of
+/// the parent directories of `file`. If the configured style root is encountered, the style file
+/// placed there takes precedence over any existing style file in the same directory.
+pub fn lookup(
+    file: &path::Path,
+    style_and_root: &Option<(path::PathBuf, path::PathBuf)>,
+) -> Option<StyleLookup> {
+    for dir in file.ancestors().skip(1) {
+        if let Some((style_file, style_root)) = style_and_root {
+            if dir == style_root {
+                return Some(StyleLookup::Placed {
+                    style_file: style_file.clone(),
+                    dst: dir.join(STYLE_FILE_NAMES[0]),
+                });
+            }
+        }
+        for name in STYLE_FILE_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return Some(StyleLookup::Existing(candidate));
+            }
+        }
+    }
+    None
+}
+
+/// Provides the configuration of `style_file` as reported by `clang-format --dump-config` for a
+/// file with the extension `ext`, evaluating the style file in isolation by placing it into a
+/// temporary directory.
+pub fn dump_isolated(
+    cmd: &cmd::Runner,
+    style_file: &path::Path,
+    ext: &str,
+) -> eyre::Result<String> {
+    let dir = tempfile::Builder::new()
+        .prefix("run-clang-format")
+        .tempdir()
+        .wrap_err("Failed to create temporary directory")?;
+    fs::copy(style_file, dir.path().join(STYLE_FILE_NAMES[0]))
+        .wrap_err("Failed to copy the style file")?;
+    Ok(cmd.dump_config(dir.path().join(format!("style.{ext}")))?)
+}
+
+/// The style configuration that clang-format uses for a file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "source", rename_all = "camelCase")]
+pub enum StyleSource {
+    /// The configured `styleFile`, placed in the `styleRoot` directory as `placed_as`.
+    #[serde(rename_all = "camelCase")]
+    Placed {
+        style_file: path::PathBuf,
+        placed_as: path::PathBuf,
+    },
+    /// A style file that exists within the parent directories of the file.
+    #[serde(rename_all = "camelCase")]
+    Existing { style_file: path::PathBuf },
+    /// A style passed to clang-format as argument, e.g., `--style=LLVM`.
+    Argument { style: String },
+    /// No style file is found, clang-format uses the fallback style, e.g., `none`.
+    Fallback { style: String },
+}
+
+impl StyleSource {
+    /// Determines the style that clang-format uses for `file` when executed with the arguments
+    /// `args`: A style argument takes precedence over any style file, otherwise the first style
+    /// file found in the parent directories of `file` is used, see [`lookup`].
+    pub fn resolve(
+        file: &path::Path,
+        style_and_root: &Option<(path::PathBuf, path::PathBuf)>,
+        args: &[String],
+    ) -> StyleSource {
+        let value = |name: &str| {
+            args.iter().rev().find_map(|arg| {
+                let (arg_name, value) = arg.trim_start_matches('-').split_once('=')?;
+                (arg_name == name).then(|| value.to_string())
+            })
+        };
+
+        if let Some(style) = value("style").filter(|style| style != "file") {
+            return StyleSource::Argument { style };
+        }
+        match lookup(file, style_and_root) {
+            Some(StyleLookup::Placed { style_file, dst }) => StyleSource::Placed {
+                style_file,
+                placed_as: dst,
+            },
+            Some(StyleLookup::Existing(style_file)) => StyleSource::Existing { style_file },
+            // the default fallback style passed by the tool is `none`
+            None => StyleSource::Fallback {
+                style: value("fallback-style").unwrap_or_else(|| "none".to_string()),
+            },
+        }
+    }
+
+    /// Applies `f` to all paths, e.g., to provide paths relative to the configuration file.
+    pub fn map_paths<F>(self, f: F) -> StyleSource
+    where
+        F: Fn(&path::Path) -> path::PathBuf,
+    {
+        match self {
+            StyleSource::Placed {
+                style_file,
+                placed_as,
+            } => StyleSource::Placed {
+                style_file: f(&style_file),
+                placed_as: f(&placed_as),
+            },
+            StyleSource::Existing { style_file } => StyleSource::Existing {
+                style_file: f(&style_file),
+            },
+            source => source,
+        }
+    }
+
+    /// The style file that clang-format is expected to load, if any.
+    pub fn style_file(&self) -> Option<&path::Path> {
+        match self {
+            StyleSource::Placed { style_file, .. } | StyleSource::Existing { style_file } => {
+                Some(style_file)
+            }
+            StyleSource::Argument { .. } | StyleSource::Fallback { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for StyleSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleSource::Placed {
+                style_file,
+                placed_as,
+            } => write!(
+                f,
+                "{} (copy of {})",
+                placed_as.to_string_lossy(),
+                style_file.to_string_lossy()
+            ),
+            StyleSource::Existing { style_file } => write!(f, "{}", style_file.to_string_lossy()),
+            StyleSource::Argument { style } => {
+                write!(f, "'--style={style}' passed to clang-format")
+            }
+            StyleSource::Fallback { style } => write!(f, "no style file, fallback style '{style}'"),
+        }
+    }
+}
+
+/// Finds existing style files within the style root that take precedence over the placed style
+/// file, i.e., for each such style file the `paths` that clang-format formats with a different
+/// style than the configured one.
+pub fn shadowing<'a, I>(
+    paths: I,
+    style_and_root: &Option<(path::PathBuf, path::PathBuf)>,
+) -> BTreeMap<path::PathBuf, Vec<path::PathBuf>>
+where
+    I: IntoIterator<Item = &'a path::PathBuf>,
+{
+    let mut shadowing: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    let style_root = match style_and_root {
+        None => return shadowing,
+        Some((_, style_root)) => style_root,
+    };
+    for path in paths.into_iter().filter(|p| p.starts_with(style_root)) {
+        if let Some(StyleLookup::Existing(style)) = lookup(path, style_and_root) {
+            shadowing.entry(style).or_default().push(path.clone());
+        }
+    }
+    shadowing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let root = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test-files");
+        let file = root.join("c-demo/pkg_a/module_a/module_a.c");
+
+        let style_and_root = Some((
+            root.join("clang-format/named.clang-format"),
+            root.join("c-demo"),
+        ));
+        assert_eq!(
+            Some(StyleLookup::Placed {
+                style_file: root.join("clang-format/named.clang-format"),
+                dst: root.join("c-demo/.clang-format"),
+            }),
+            lookup(&file, &style_and_root)
+        );
+
+        // files outside of the style root use the next existing style file
+        let file = root.join("clang-format/some.file");
+        assert_eq!(
+            Some(StyleLookup::Existing(
+                root.join("clang-format/.clang-format")
+            )),
+            lookup(&file, &style_and_root)
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let root = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test-files");
+        let file = root.join("c-demo/pkg_a/module_a/module_a.c");
+
+        let placed = StyleSource::Placed {
+            style_file: root.join("clang-format/named.clang-format"),
+            placed_as: root.join("c-demo/.clang-format"),
+        };
+        let style_and_root = Some((
+            root.join("clang-format/named.clang-format"),
+            root.join("c-demo"),
+        ));
+        assert_eq!(placed, StyleSource::resolve(&file, &style_and_root, &[]));
+
+        // style arguments take precedence over style files, the last argument wins
+        let args = vec!["--style=file".to_string()];
+        assert_eq!(placed, StyleSource::resolve(&file, &style_and_root, &args));
+        let args = vec!["--style=LLVM".to_string(), "-style=Google".to_string()];
+        assert_eq!(
+            StyleSource::Argument {
+                style: "Google".into()
+            },
+            StyleSource::resolve(&file, &style_and_root, &args)
+        );
+
+        // without style files, the fallback style is used
+        let file = path::Path::new("/some.c");
+        assert_eq!(
+            StyleSource::Fallback {
+                style: "none".into()
+            },
+            StyleSource::resolve(file, &None, &[])
+        );
+        let args = vec!["--fallback-style=LLVM".to_string()];
+        assert_eq!(
+            StyleSource::Fallback {
+                style: "LLVM".into()
+            },
+            StyleSource::resolve(file, &None, &args)
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeSet, fs};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli, resolve, style};
+
+/// Fields that are not part of the configuration, but are accepted for documentation purposes.
+const ACCEPTED_FIELDS: [&str; 2] = ["$schema", "description"];
+
+fn unknown_fields(data: &cli::Data) -> eyre::Result<Vec<String>> {
+    let content = fs::read_to_string(&data.json.name)
+        .wrap_err(format!("Failed to read '{}'", data.json.name))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).wrap_err(format!("Failed to parse '{}'", data.json.name))?;
+
+    let known = cli::JsonModel::field_names();
+    Ok(value
+        .as_object()
+        .map(|object| {
+            object
+                .keys()
+                .filter(|key| !known.contains(key) && !ACCEPTED_FIELDS.contains(&key.as_str()))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Patterns of `field` that did not match any path.
+fn unused(field: &str, patterns: &Option<Vec<String>>, hits: &[usize]) -> Vec<String> {
+    patterns
+        .iter()
+        .flatten()
+        .zip(hits.iter())
+        .filter(|(_, hits)| **hits == 0)
+        .map(|(pattern, _)| format!("'{pattern}' in '{field}' does not match any path"))
+        .collect()
+}
+
+/// Loads the configuration and reports problems without formatting any files.
+pub fn run(data: &cli::Data, strict: bool) -> eyre::Result<()> {
+    let mut warnings = vec![];
+
+    match unknown_fields(data) {
+        Err(err) => warnings.push(format!("{err:#}")),
+        Ok(fields) => {
+            warnings.extend(fields.into_iter().map(|field| {
+                format!("Unknown field '{field}', the field is ignored when formatting")
+            }))
+        }
+    }
+
+    let style_and_root = resolve::style_and_root(data);
+    if let Err(err) = &style_and_root {
+        warnings.push(format!("{err:#}"));
+    }
+    let style_and_root = style_and_root.unwrap_or(None);
+
+    let selection = crate::select_paths(data);
+    match &selection {
+        Err(err) => warnings.push(format!("{err:#}")),
+        Ok(selection) => {
+            let paths = Some(data.json.paths.clone());
+            warnings.extend(unused("paths", &paths, &selection.hits.paths));
+            warnings.extend(unused(
+                "filterPre",
+                &data.json.filter_pre,
+                &selection.hits.filter_pre,
+            ));
+            warnings.extend(unused(
+                "filterPost",
+                &data.json.filter_post,
+                &selection.hits.filter_post,
+            ));
+        }
+    }
+
+    match crate::get_command(data) {
+        Err(err) => warnings.push(format!("{err:#}")),
+        Ok(cmd) => {
+            if let Some((style_file, _)) = &style_and_root {
+                if let Err(err) = style::dump_isolated(&cmd, style_file, "cpp") {
+                    warnings.push(format!(
+                        "clang-format fails to parse the style file '{}': {err:#}",
+                        style_file.to_string_lossy()
+                    ));
+                }
+            }
+
+            // existing style files are checked for the first file that uses it
+            let mut checked = BTreeSet::new();
+            for file in selection.iter().flat_map(|s| s.paths.iter()) {
+                let style = match style::lookup(file, &style_and_root) {
+                    Some(style::StyleLookup::Existing(style)) => style,
+                    _ => continue,
+                };
+                if !checked.insert(style.clone()) {
+                    continue;
+                }
+                if let Err(err) = cmd.dump_config(file) {
+                    warnings.push(format!(
+                        "clang-format fails to parse the style file '{}': {err:#}",
+                        style.to_string_lossy()
+                    ));
+                }
+            }
+        }
+    }
+
+    if warnings.is_empty() {
+        log::info!("No problems found in '{}'", data.json.name);
+        return Ok(());
+    }
+
+    for warning in warnings.iter() {
+        log::warn!("{}", warning);
+    }
+    let summary = format!("Found {} problems in '{}'", warnings.len(), data.json.name);
+    if strict {
+        return Err(eyre!(summary))
+            .suggestion("Please fix the reported problems or execute without '--strict'");
+    }
+    log::warn!("{}", summary);
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::{collections::BTreeMap, fs, path, thread, time};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli, globs, resolve};
+
+/// Modification time and size of a file, or `None` if the file does not exist.
+type Stamp = Option<(time::SystemTime, u64)>;
+
+fn stamp(path: &path::Path) -> Stamp {
+    fs::metadata(path)
+        .ok()
+        .map(|m| (m.modified().unwrap_or(time::UNIX_EPOCH), m.len()))
+}
+
+fn stamps<'a>(paths: impl Iterator<Item = &'a path::PathBuf>) -> BTreeMap<path::PathBuf, Stamp> {
+    paths.map(|path| (path.clone(), stamp(path))).collect()
+}
+
+/// Files that have been changed since their stamps have been taken. Files that have been removed
+/// are not included, since there is nothing to format, but are recorded as removed such that they
+/// are changed once they are created again.
+fn changed(files: &mut BTreeMap<path::PathBuf, Stamp>) -> Vec<path::PathBuf> {
+    let mut changed = vec![];
+    for (path, old) in files.iter_mut() {
+        match stamp(path) {
+            None => *old = None,
+            new if new != *old => changed.push(path.clone()),
+            _ => (),
+        }
+    }
+    changed
+}
+
+/// Updates the stamps of the `paths` that have been processed.
+fn restamp(files: &mut BTreeMap<path::PathBuf, Stamp>, paths: &[path::PathBuf]) {
+    for path in paths {
+        files.insert(path.clone(), stamp(path));
+    }
+}
+
+/// State of all files that are watched for a configuration.
+struct Watched {
+    /// The configuration file.
+    config: (path::PathBuf, Stamp),
+    /// The style file, if specified.
+    style: Option<(path::PathBuf, Stamp)>,
+    /// The files selected by the configuration.
+    files: BTreeMap<path::PathBuf, Stamp>,
+    /// Directories in which new files may be created: The directories of the selected files and
+    /// the leading directories of the entries in `paths`.
+    dirs: BTreeMap<path::PathBuf, Stamp>,
+}
+
+impl Watched {
+    /// Resolves the file set and the style file for the configuration `data`.
+    fn resolve(data: &cli::Data) -> eyre::Result<Watched> {
+        let config = path::PathBuf::from(&data.json.name);
+        let style = resolve::style_and_root(data)?.map(|(style, _)| (style.clone(), stamp(&style)));
+        let files = crate::select_paths(data)?.paths;
+
+        let candidates = globs::build_matchers_from(
+            &data.json.paths,
+            &data.json.root,
+            "paths",
+            &data.json.name,
+        )?;
+        let mut dirs: Vec<_> = candidates
+            .iter()
+            .filter(|m| !m.negated)
+            .filter_map(|m| globs::literal_prefix(&m.matcher).canonicalize().ok())
+            .filter(|dir| dir.is_dir())
+            .collect();
+        dirs.extend(
+            files
+                .iter()
+                .filter_map(|f| f.parent())
+                .map(path::PathBuf::from),
+        );
+
+        Ok(Watched {
+            config: (config.clone(), stamp(&config)),
+            style,
+            files: stamps(files.iter()),
+            dirs: stamps(dirs.iter()),
+        })
+    }
+}
+
+/// Formats or checks the `paths`, reporting errors without stopping to watch.
+fn execute(data: &cli::Data, paths: &[path::PathBuf]) {
+    if let Err(err) = crate::execute(data, Some(paths.to_vec())) {
+        log::error!("{err:?}");
+    }
+}
+
+/// Formats or checks all files selected by the configuration `data`, and then polls the files,
+/// the configuration and the style file for changes in the given `interval`. Only changed files
+/// are processed again, unless the configuration or the style file changed. Runs until
+/// interrupted.
+pub fn run(mut data: cli::Data, check: bool, interval: time::Duration) -> eyre::Result<()> {
+    data.cmd = match check {
+        true => cli::Command::Check { fix: false },
+        false => cli::Command::Format,
+    };
+    // each cycle would create a backup run, replacing the backups of actual format runs
+    data.backup = false;
+    crate::setup_jobs(data.jobs)?;
+
+    let mut watched = Watched::resolve(&data)?;
+    let paths: Vec<_> = watched.files.keys().cloned().collect();
+    execute(&data, &paths);
+    restamp(&mut watched.files, &paths);
+    log::info!(
+        "Watching {} files for changes, press Ctrl+C to stop",
+        watched.files.len()
+    );
+
+    loop {
+        thread::sleep(interval);
+
+        let (config, config_stamp) = &watched.config;
+        let paths = if stamp(config) != *config_stamp {
+            // the file set is recomputed and processed entirely if the configuration changed
+            log::info!("Configuration {} changed", config.to_string_lossy());
+            let reloaded = cli::JsonModel::load(config)
+                .wrap_err("Invalid configuration file")
+                .and_then(|json| {
+                    let previous = std::mem::replace(&mut data.json, json);
+                    Watched::resolve(&data).inspect_err(|_| data.json = previous)
+                });
+            match reloaded {
+                Ok(reloaded) => watched = reloaded,
+                Err(err) => {
+                    // the previous configuration is kept until the configuration is fixed
+                    log::error!("{err:?}");
+                    watched.config.1 = stamp(&watched.config.0);
+                    continue;
+                }
+            }
+            watched.files.keys().cloned().collect()
+        } else if watched
+            .style
+            .as_ref()
+            .is_some_and(|(style, style_stamp)| stamp(style) != *style_stamp)
+        {
+            log::info!("Style file changed");
+            if let Some((style, style_stamp)) = watched.style.as_mut() {
+                *style_stamp = stamp(style);
+            }
+            watched.files.keys().cloned().collect()
+        } else if watched.dirs.iter().any(|(dir, old)| stamp(dir) != *old) {
+            // files have been created or removed, the stamps of known files are kept such that
+            // only new and changed files are processed, and the stamps of the configuration and
+            // the style file such that their changes are not missed
+            match Watched::resolve(&data) {
+                Ok(resolved) => {
+                    watched.files = resolved
+                        .files
+                        .into_keys()
+                        .map(|path| {
+                            let stamp = watched.files.get(&path).copied().flatten();
+                            (path, stamp)
+                        })
+                        .collect();
+                    watched.dirs = resolved.dirs;
+                }
+                Err(err) => {
+                    log::error!("{err:?}");
+                    watched.dirs = stamps(watched.dirs.keys());
+                    continue;
+                }
+            }
+            changed(&mut watched.files)
+        } else {
+            changed(&mut watched.files)
+        };
+
+        if paths.is_empty() {
+            continue;
+        }
+        execute(&data, &paths);
+        // files changed by formatting must not trigger another run, files that have been changed
+        // while executing are processed in the next cycle
+        restamp(&mut watched.files, &paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let tmp = tempfile::Builder::new().prefix("watch").tempdir().unwrap();
+        let a = tmp.path().join("a.c");
+        let b = tmp.path().join("b.c");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+
+        let mut files = stamps([a.clone(), b.clone()].iter());
+        assert!(changed(&mut files).is_empty());
+
+        fs::write(&a, "aa").unwrap();
+        fs::remove_file(&b).unwrap();
+        assert_eq!(vec![a.clone()], changed(&mut files));
+
+        // only processed files are restamped
+        restamp(&mut files, std::slice::from_ref(&a));
+        assert!(changed(&mut files).is_empty());
+
+        // removed files are changed once they are created again, regardless of their stamp
+        fs::write(&b, "b").unwrap();
+        assert_eq!(vec![b], changed(&mut files));
+    }
+}
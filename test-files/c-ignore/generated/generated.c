@@ -0,0 +1,14 @@
+/*
+ * Copyright (c) 2024 Martin Lampacher. All rights reserved.
+ */
+
+#include "module_nofmt.h"
+
+/***********************************************************************************************************************
+ * Functions
+ **********************************************************************************************************************/
+
+void module_nofmt_init      (void)
+   {
+       // nothing to see here
+ }
@@ -0,0 +1,14 @@
+/*
+ * Copyright (c) 2024 Martin Lampacher. All rights reserved.
+ */
+
+#include "module.h"
+
+/***********************************************************************************************************************
+ * Functions
+ **********************************************************************************************************************/
+
+void module_init (void)
+{
+    // nothing to see here
+}
@@ -0,0 +1,8 @@
+/*
+ * Copyright (c) 2024 Martin Lampacher. All rights reserved.
+ */
+
+template <typename T> inline T add(T a, T b)
+{
+    return a + b;
+}
@@ -0,0 +1,4 @@
+#!/bin/sh
+# stands in for an environment that provides clang-format at a path that does not exist on the host
+shift
+exec "$(dirname "$0")/../../artifacts/clang/clang-format" "$@"
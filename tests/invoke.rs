@@ -57,6 +57,22 @@ fn run_cmd_and_assert(cmd: &mut Command, should_pass: bool) {
     assert_eq!(output.status.success(), should_pass);
 }
 
+fn run_cmd_and_assert_output(cmd: &mut Command, should_pass: bool, expected: &str) {
+    let output = cmd.output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    if output.status.success() != should_pass || !format!("{stdout}{stderr}").contains(expected) {
+        println!("status: {}", output.status);
+        println!("{stdout}");
+        println!("{stderr}");
+    }
+
+    assert_eq!(output.status.success(), should_pass);
+    assert!(format!("{stdout}{stderr}").contains(expected));
+}
+
 #[test]
 fn invoke_json_and_bin() {
     // empty .json file is not accepted
@@ -273,3 +289,798 @@ fn invoke_strict_root_err() {
         false,
     );
 }
+
+#[test]
+#[cfg(not(windows))]
+fn invoke_launcher() {
+    // given: a valid .json configuration file with a launcher
+    let json = crate_root_rel("test-files/json/test-ok-launcher.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--check"), true);
+
+    // paired with an invalid --launcher parameter, leads to an error (overrides valid .json)
+    run_cmd_and_assert(
+        cmd().arg(json.as_os_str()).arg("--launcher=i/do/not/exist"),
+        false,
+    );
+
+    // a launcher that does not execute the command fails the validation
+    run_cmd_and_assert(
+        cmd().arg(json.as_os_str()).arg("--launcher=/usr/bin/false"),
+        false,
+    );
+
+    // the launcher is also accepted as command-line parameter
+    let json = crate_root_rel("test-files/json/test-ok-style-and-command.json");
+    run_cmd_and_assert(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--launcher=/usr/bin/env LC_ALL=C"),
+        true,
+    );
+
+    let json = crate_root_rel("test-files/json/test-err-invalid-launcher.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()), false);
+
+    // with a launcher, the command only needs to exist within the launcher's environment
+    let json = crate_root_rel("test-files/json/test-err-invalid-command.json");
+    let script = crate_root().join("test-files/launcher/container.sh");
+    run_cmd_and_assert(
+        cmd()
+            .arg(json.as_os_str())
+            .arg(format!("--launcher=/bin/sh {}", script.to_string_lossy())),
+        true,
+    );
+}
+
+#[test]
+fn invoke_extra_args() {
+    let json = crate_root_rel("test-files/json/test-ok-format.json");
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--check")
+            .arg("--")
+            .arg("--sort-includes=false")
+            .arg("-fallback-style=LLVM"),
+        true,
+    );
+
+    // options that are controlled by the tool are rejected
+    for arg in ["-i", "--dry-run", "-n", "some/file.c"] {
+        run_cmd_and_assert(
+            cmd_with_path()
+                .arg(json.as_os_str())
+                .arg("--check")
+                .arg("--")
+                .arg(arg),
+            false,
+        );
+    }
+
+    let json = crate_root_rel("test-files/json/test-err-extra-args.json");
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()).arg("--check"), false);
+
+    // arguments passed on the command-line override the 'extraArgs' of the configuration
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--check")
+            .arg("--")
+            .arg("--sort-includes=false"),
+        true,
+    );
+}
+
+#[test]
+fn invoke_language_map() {
+    let json = crate_root_rel("test-files/json/test-ok-language-map.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--check"), true);
+    run_cmd_and_assert(cmd().arg(json.as_os_str()), true);
+
+    let json = crate_root_rel("test-files/json/test-err-language-map.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--check"), false);
+
+    let json = crate_root_rel("test-files/json/test-err-invalid-language.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--check"), false);
+}
+
+#[test]
+fn invoke_extensions() {
+    // directories are expanded and only files with the extension "c" are used
+    let json = crate_root_rel("test-files/json/test-ok-extensions.json");
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--check"),
+        true,
+        "Found 3 files",
+    );
+}
+
+#[test]
+fn invoke_negated_paths() {
+    let json = crate_root_rel("test-files/json/test-ok-negated.json");
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--check"),
+        true,
+        "Found 10 files",
+    );
+}
+
+#[test]
+fn invoke_list() {
+    let json = crate_root_rel("test-files/json/test-ok-negated.json");
+    run_cmd_and_assert_output(
+        cmd().arg("list").arg(json.as_os_str()),
+        true,
+        "../c-demo/project/src/main.c",
+    );
+    run_cmd_and_assert_output(
+        cmd()
+            .arg("list")
+            .arg(json.as_os_str())
+            .arg("--show-filtered"),
+        true,
+        "!../c-demo/pkg_b/module_b/module_b.c (negated)",
+    );
+    run_cmd_and_assert_output(
+        cmd()
+            .arg("list")
+            .arg(json.as_os_str())
+            .arg("--json")
+            .arg("--show-filtered"),
+        true,
+        "\"reason\": \"negated\"",
+    );
+    // the style is reported for each file and confirmed using clang-format
+    run_cmd_and_assert_output(
+        cmd().arg("list").arg(json.as_os_str()).arg("--json"),
+        true,
+        "\"placedAs\": \"../c-demo/.clang-format\"",
+    );
+    run_cmd_and_assert_output(
+        cmd().arg("list").arg(json.as_os_str()).arg("--json"),
+        true,
+        "\"confirmed\": true",
+    );
+
+    // clang-format does not load a nested style file that it fails to parse
+    let (_tmp, nested) = project_with(&[
+        ("src/main.c", CLEAN),
+        ("src/nested/nested.c", CLEAN),
+        ("src/nested/.clang-format", "SortIncludes: trueX\n"),
+    ]);
+    run_cmd_and_assert_output(
+        cmd().arg("list").arg(nested.as_os_str()).arg("--json"),
+        true,
+        "\"confirmed\": false",
+    );
+
+    // the list of files is resolved without executing clang-format
+    let json = crate_root_rel("test-files/json/test-err-invalid-command.json");
+    run_cmd_and_assert(cmd().arg("list").arg(json.as_os_str()), true);
+
+    let json = crate_root_rel("test-files/json/test-err-invalid-glob.json");
+    run_cmd_and_assert(cmd().arg("list").arg(json.as_os_str()), false);
+}
+
+#[test]
+fn invoke_explain() {
+    let json = crate_root_rel("test-files/json/test-ok-negated.json");
+    let explain = |path: &str| {
+        let mut cmd = cmd();
+        cmd.arg("explain")
+            .arg(json.as_os_str())
+            .arg(crate_root_rel(path).as_os_str());
+        cmd
+    };
+
+    run_cmd_and_assert_output(
+        &mut explain("test-files/c-demo/pkg_b/module_c/module_c.c"),
+        true,
+        "selected",
+    );
+    run_cmd_and_assert_output(
+        &mut explain("test-files/c-demo/pkg_b/module_b/module_b.c"),
+        true,
+        "excluded by a negated entry in 'paths'",
+    );
+    run_cmd_and_assert_output(
+        &mut explain("test-files/c-demo/.hidden/hidden.c"),
+        true,
+        "hidden paths are skipped",
+    );
+    run_cmd_and_assert_output(
+        &mut explain("test-files/c-demo/project/src/main.c"),
+        true,
+        "../c-demo/.clang-format (copy of ../clang-format/named.clang-format)",
+    );
+    run_cmd_and_assert(&mut explain("test-files/i/do/not/exist.c"), false);
+}
+
+#[test]
+fn invoke_validate() {
+    run_cmd_and_assert_output(
+        cmd()
+            .arg("validate")
+            .arg("--strict")
+            .arg(crate_root_rel("test-files/json/test-ok-negated.json")),
+        true,
+        "No problems found",
+    );
+
+    // problems are only reported as warnings unless '--strict' is used
+    let json = crate_root_rel("test-files/json/test-ok-validate.json");
+    for expected in [
+        "Unknown field 'formatStyle'",
+        "'../c-demo/**/*.cpp' in 'paths' does not match any path",
+        "'**/does_not_exist/**' in 'filterPost' does not match any path",
+        "fails to parse the style file",
+    ] {
+        run_cmd_and_assert_output(cmd().arg("validate").arg(json.as_os_str()), true, expected);
+    }
+    run_cmd_and_assert(
+        cmd().arg("validate").arg("--strict").arg(json.as_os_str()),
+        false,
+    );
+}
+
+/// Content of a formatted file, and of a file that is changed by formatting.
+const CLEAN: &str = "int main(void)\n";
+const DIRTY: &str = "int main(void)   \n";
+
+/// Creates a temporary project with the `files` and a configuration `format.json` for all `.c`
+/// files in `src`, using the style root `src`. Provides the directory and the configuration.
+fn project_with(files: &[(&str, &str)]) -> (tempfile::TempDir, path::PathBuf) {
+    let tmp = tempfile::Builder::new()
+        .prefix("project")
+        .tempdir()
+        .unwrap();
+    let dir = tmp.path();
+    for (file, content) in files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    std::fs::copy(
+        crate_root_rel("test-files/clang-format/named.clang-format"),
+        dir.join("named.clang-format"),
+    )
+    .unwrap();
+    let command = crate_root_rel("artifacts/clang/clang-format");
+    let config = format!(
+        r#"{{"paths": ["src/**/*.c"], "styleFile": "named.clang-format", "styleRoot": "src", "command": {:?}}}"#,
+        command.to_string_lossy()
+    );
+    let json = dir.join("format.json");
+    std::fs::write(&json, config).unwrap();
+    (tmp, json)
+}
+
+/// Creates a temporary project with the formatted file `src/clean.c` and the file `src/dirty.c`.
+fn project() -> (tempfile::TempDir, path::PathBuf) {
+    project_with(&[("src/clean.c", CLEAN), ("src/dirty.c", DIRTY)])
+}
+
+/// Creates a temporary project with a style file in the style root `src` that is shadowed by the
+/// style file in `src/nested`.
+fn shadowing_project() -> (tempfile::TempDir, path::PathBuf) {
+    project_with(&[
+        ("src/main.c", ""),
+        ("src/nested/nested.c", ""),
+        ("src/nested/.clang-format", ""),
+    ])
+}
+
+/// Sets the modification time of the file at `path` into the past, such that rewriting the file
+/// can be detected. Provides the modification time.
+fn backdate(path: &path::Path) -> time::SystemTime {
+    let mtime = time::SystemTime::UNIX_EPOCH + time::Duration::from_secs(1_000_000);
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    mtime
+}
+
+fn read(path: &path::Path) -> String {
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn invoke_doctor() {
+    let json = crate_root_rel("test-files/json/test-ok-negated.json");
+    for expected in [
+        "clang-format version",
+        "--dump-config: supported",
+        "No problems found",
+    ] {
+        run_cmd_and_assert_output(cmd().arg("doctor").arg(json.as_os_str()), true, expected);
+    }
+
+    // style files within the style root shadow the placed style file
+    let (_tmp, json) = shadowing_project();
+    run_cmd_and_assert_output(
+        cmd().arg("doctor").arg(json.as_os_str()),
+        true,
+        "The style file src/nested/.clang-format shadows the placed style file for 1 files",
+    );
+}
+
+#[test]
+fn invoke_strict_style() {
+    let (tmp, json) = shadowing_project();
+
+    // shadowing style files are reported, but only fail in strict mode
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()),
+        true,
+        "takes precedence over the placed style file for 1 files",
+    );
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()),
+        true,
+        "nested/.clang-format for 1 files",
+    );
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--strict-style"), false);
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--strict-root"), false);
+
+    std::fs::remove_file(tmp.path().join("src/nested/.clang-format")).unwrap();
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--strict-style"), true);
+
+    // the check requires a style root
+    let json = crate_root_rel("test-files/json/test-ok-empty-paths.json");
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--strict-style"),
+        false,
+        "Missing style root for strict style check",
+    );
+}
+
+#[test]
+fn invoke_list_changed() {
+    let (tmp, json) = project();
+    let dirty = tmp.path().join("src/dirty.c");
+
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()),
+        true,
+        "Reformatted 1 of 2 files",
+    );
+    assert_eq!(CLEAN, read(&dirty));
+
+    // only the changed files are printed
+    std::fs::write(&dirty, DIRTY).unwrap();
+    let output = cmd()
+        .arg(json.as_os_str())
+        .arg("--list-changed")
+        .arg("-q")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let expected = path::Path::new("src").join("dirty.c");
+    assert_eq!(
+        format!("{}\n", expected.to_string_lossy()),
+        String::from_utf8(output.stdout).unwrap()
+    );
+
+    run_cmd_and_assert(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--list-changed")
+            .arg("--check"),
+        false,
+    );
+}
+
+#[test]
+fn invoke_check_fix() {
+    let (tmp, json) = project();
+    let (clean, dirty) = (
+        tmp.path().join("src/clean.c"),
+        tmp.path().join("src/dirty.c"),
+    );
+
+    // the modification time reveals whether a file has been rewritten
+    let mtime = backdate(&clean);
+
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--fix"), false);
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--check").arg("--fix"),
+        false,
+        "Fixed the format of the following files",
+    );
+    assert_eq!(CLEAN, read(&dirty));
+    assert_eq!(mtime, clean.metadata().unwrap().modified().unwrap());
+
+    // nothing to fix
+    run_cmd_and_assert(
+        cmd().arg(json.as_os_str()).arg("--check").arg("--fix"),
+        true,
+    );
+}
+
+#[test]
+fn invoke_emit_patch() {
+    let (tmp, json) = project();
+    let dir = tmp.path();
+
+    // paths in the patch are relative to the working directory
+    run_cmd_and_assert_output(
+        cmd()
+            .current_dir(dir)
+            .arg("format.json")
+            .arg("--emit-patch")
+            .arg("fix.patch"),
+        true,
+        "Created patch fix.patch for 1 of 2 files",
+    );
+    assert_eq!(
+        "diff --git a/src/dirty.c b/src/dirty.c\n\
+         --- a/src/dirty.c\n\
+         +++ b/src/dirty.c\n\
+         @@ -1 +1 @@\n\
+         -int main(void)   \n\
+         +int main(void)\n",
+        read(&dir.join("fix.patch"))
+    );
+    // the files remain untouched
+    assert_eq!(DIRTY, read(&dir.join("src/dirty.c")));
+
+    run_cmd_and_assert(
+        cmd()
+            .current_dir(dir)
+            .arg("format.json")
+            .arg("--emit-patch")
+            .arg("fix.patch")
+            .arg("--check"),
+        false,
+    );
+    // files outside the working directory cannot be patched
+    std::fs::create_dir(dir.join("lib")).unwrap();
+    run_cmd_and_assert_output(
+        cmd()
+            .current_dir(dir.join("lib"))
+            .arg(json.as_os_str())
+            .arg("--emit-patch")
+            .arg("fix.patch"),
+        false,
+        "outside the working directory",
+    );
+
+    // no patch is created if all files are formatted, a stale patch is removed
+    run_cmd_and_assert(cmd().arg(json.as_os_str()), true);
+    run_cmd_and_assert_output(
+        cmd()
+            .current_dir(dir)
+            .arg("format.json")
+            .arg("--emit-patch")
+            .arg("fix.patch"),
+        true,
+        "no patch created",
+    );
+    assert!(!dir.join("fix.patch").exists());
+}
+
+#[test]
+fn invoke_output_dir() {
+    let (tmp, json) = project_with(&[("src/clean.c", CLEAN), ("src/module/dirty.c", DIRTY)]);
+    let dir = tmp.path();
+    let out = dir.join("out");
+
+    run_cmd_and_assert_output(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--output-dir")
+            .arg(out.as_os_str()),
+        true,
+        "Reformatted 1 of 2 files",
+    );
+    assert_eq!(CLEAN, read(&out.join("src/module/dirty.c")));
+    assert_eq!(CLEAN, read(&out.join("src/clean.c")));
+    // the sources remain untouched
+    assert_eq!(DIRTY, read(&dir.join("src/module/dirty.c")));
+
+    // files outside the directory of the configuration cannot be mirrored
+    let json = crate_root_rel("test-files/json/test-ok-format.json");
+    run_cmd_and_assert_output(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--output-dir")
+            .arg(out.as_os_str()),
+        false,
+        "outside the directory of the configuration file",
+    );
+}
+
+#[test]
+fn invoke_restore() {
+    let (tmp, json) = project();
+    let dirty = tmp.path().join("src/dirty.c");
+
+    run_cmd_and_assert(cmd().arg("restore").arg(json.as_os_str()), false);
+    run_cmd_and_assert_output(cmd().arg(json.as_os_str()), true, "Created backup");
+    assert_eq!(CLEAN, read(&dirty));
+    run_cmd_and_assert_output(
+        cmd().arg("restore").arg(json.as_os_str()).arg("--list"),
+        true,
+        "(1 files)",
+    );
+    run_cmd_and_assert(cmd().arg("restore").arg(json.as_os_str()), true);
+    assert_eq!(DIRTY, read(&dirty));
+    // the restored backup is removed
+    run_cmd_and_assert(cmd().arg("restore").arg(json.as_os_str()), false);
+
+    // files modified after formatting are only restored with --force
+    run_cmd_and_assert(cmd().arg(json.as_os_str()), true);
+    std::fs::write(&dirty, "int main(int argc)\n").unwrap();
+    run_cmd_and_assert_output(
+        cmd().arg("restore").arg(json.as_os_str()),
+        false,
+        "modified after formatting",
+    );
+    assert_eq!("int main(int argc)\n", read(&dirty));
+    run_cmd_and_assert(
+        cmd().arg("restore").arg(json.as_os_str()).arg("--force"),
+        true,
+    );
+    assert_eq!(DIRTY, read(&dirty));
+
+    // no backups are created with --no-backup, the data directory is removed with the last backup
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--no-backup"), true);
+    run_cmd_and_assert(cmd().arg("restore").arg(json.as_os_str()), false);
+    assert!(!tmp.path().join(".run-clang-format").exists());
+
+    // options that do not modify any file cannot disable backups
+    run_cmd_and_assert(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--no-backup")
+            .arg("--output-dir")
+            .arg(tmp.path().join("out").as_os_str()),
+        false,
+    );
+}
+
+#[test]
+fn invoke_verify_idempotent() {
+    let (tmp, json) = project_with(&[("src/dirty.c", DIRTY)]);
+    let dir = tmp.path();
+
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--verify-idempotent"),
+        true,
+        "Formatting is idempotent for all 1 files",
+    );
+    // the files remain untouched
+    assert_eq!(DIRTY, read(&dir.join("src/dirty.c")));
+
+    // a command that changes its own output on each execution
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let command = dir.join("unstable-format");
+        std::fs::write(
+            &command,
+            "#!/bin/sh\n\
+             case \"$*\" in *--version*) echo 'clang-format version 17.0.6'; exit 0;; esac\n\
+             sed 's/)/))/'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        run_cmd_and_assert_output(
+            cmd()
+                .arg(json.as_os_str())
+                .arg("--verify-idempotent")
+                .arg("--command")
+                .arg(command.as_os_str()),
+            false,
+            "+int main(void)))",
+        );
+    }
+}
+
+#[test]
+fn invoke_atomic_write() {
+    let (tmp, json) = project();
+    let (clean, dirty) = (
+        tmp.path().join("src/clean.c"),
+        tmp.path().join("src/dirty.c"),
+    );
+
+    // unchanged files are not written
+    let mtime = backdate(&clean);
+
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--atomic-write"),
+        true,
+        "Reformatted 1 of 2 files",
+    );
+    assert_eq!(CLEAN, read(&dirty));
+    assert_eq!(mtime, clean.metadata().unwrap().modified().unwrap());
+
+    // options that do not modify any file cannot write atomically
+    run_cmd_and_assert(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--atomic-write")
+            .arg("--verify-idempotent"),
+        false,
+    );
+}
+
+#[test]
+fn invoke_stdin_filename() {
+    let (tmp, json) = project_with(&[("src/main.c", DIRTY), ("lib/lib.c", "")]);
+    let dir = tmp.path();
+
+    let output = |file: &str| {
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--stdin-filename")
+            .arg(dir.join(file).as_os_str())
+            .write_stdin(DIRTY)
+            .output()
+            .unwrap()
+    };
+
+    // selected files are formatted, the file itself remains unchanged
+    let out = output("src/main.c");
+    assert!(out.status.success());
+    assert_eq!(CLEAN, String::from_utf8_lossy(&out.stdout));
+    assert_eq!(DIRTY, read(&dir.join("src/main.c")));
+    // the style file is passed to clang-format instead of placing it
+    assert!(!dir.join("src/.clang-format").exists());
+
+    // new files that have not been saved yet are selected by their path
+    let out = output("src/./new.c");
+    assert!(out.status.success());
+    assert_eq!(CLEAN, String::from_utf8_lossy(&out.stdout));
+    assert!(!dir.join("src/new.c").exists());
+
+    // files that are not selected, or within directories that do not exist, are passed through
+    for file in ["lib/lib.c", "lib/new.c", "src/new.h", "src/new/new.c"] {
+        let out = output(file);
+        assert!(out.status.success());
+        assert_eq!(DIRTY, String::from_utf8_lossy(&out.stdout));
+    }
+
+    // ignore files also apply to new files
+    std::fs::write(dir.join("src/.clang-format-ignore"), "ignored.c\n").unwrap();
+    let config = read(&json).replacen('{', r#"{"respectIgnoreFiles": true, "#, 1);
+    std::fs::write(&json, config).unwrap();
+    for (file, expected) in [("src/ignored.c", DIRTY), ("src/new.c", CLEAN)] {
+        let out = output(file);
+        assert!(out.status.success());
+        assert_eq!(expected, String::from_utf8_lossy(&out.stdout));
+    }
+}
+
+/// Waits until the file at `path` has the given `content`, failing after a timeout.
+fn wait_for_content(path: &path::Path, content: &str) {
+    let start = time::Instant::now();
+    while std::fs::read_to_string(path).ok().as_deref() != Some(content) {
+        assert!(
+            start.elapsed() < time::Duration::from_secs(30),
+            "timeout while waiting for {}",
+            path.to_string_lossy()
+        );
+        thread::sleep(time::Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn invoke_watch() {
+    let (tmp, json) = project_with(&[("src/main.c", DIRTY)]);
+    let dir = tmp.path();
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin(crate_name!()))
+        .env_clear()
+        .arg("watch")
+        .arg(json.as_os_str())
+        .args(["--interval", "20"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let _kill = scopeguard::guard((), |_| {
+        let _ = child.kill();
+        let _ = child.wait();
+    });
+
+    // all files are formatted initially, and again when they change
+    let main = dir.join("src/main.c");
+    wait_for_content(&main, CLEAN);
+    std::fs::write(&main, "int main(void)     \n").unwrap();
+    wait_for_content(&main, CLEAN);
+
+    // new files in watched directories are picked up
+    let other = dir.join("src/other.c");
+    std::fs::write(&other, DIRTY).unwrap();
+    wait_for_content(&other, CLEAN);
+
+    // the file set is recomputed if the configuration changes
+    let header = dir.join("src/main.h");
+    std::fs::write(&header, "int main(void);   \n").unwrap();
+    let config = read(&json);
+    std::fs::write(
+        &json,
+        config.replace(r#""src/**/*.c""#, r#""src/**/*.c", "src/**/*.h""#),
+    )
+    .unwrap();
+    wait_for_content(&header, "int main(void);\n");
+}
+
+#[test]
+fn invoke_init() {
+    let tmp = tempfile::Builder::new().prefix("init").tempdir().unwrap();
+    let dir = tmp.path();
+    for file in [
+        "src/main.c",
+        "src/module/module.h",
+        "lib/lib.cpp",
+        "build/gen.c",
+    ] {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+    std::fs::create_dir_all(dir.join("style")).unwrap();
+    std::fs::copy(
+        crate_root_rel("test-files/clang-format/.clang-format"),
+        dir.join("style/.clang-format"),
+    )
+    .unwrap();
+
+    run_cmd_and_assert(cmd().arg("init").arg(dir.as_os_str()), true);
+    let json = dir.join("format.json");
+    let content = std::fs::read_to_string(&json).unwrap();
+    assert!(content.contains("\"src/**/*.{c,h}\""));
+    assert!(content.contains("\"styleFile\": \"style/.clang-format\""));
+
+    // the created configuration is valid, the build folder is skipped
+    run_cmd_and_assert_output(
+        cmd().arg("list").arg(json.as_os_str()),
+        true,
+        "src/module/module.h",
+    );
+    let output = cmd().arg("list").arg(json.as_os_str()).output().unwrap();
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("gen.c"));
+
+    // existing files are only overwritten with --force
+    run_cmd_and_assert(cmd().arg("init").arg(dir.as_os_str()), false);
+    run_cmd_and_assert(cmd().arg("init").arg(dir.as_os_str()).arg("--force"), true);
+}
+
+#[test]
+fn invoke_overlaps() {
+    let json = crate_root_rel("test-files/json/test-ok-overlaps.json");
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--check"),
+        true,
+        "Found 2 files",
+    );
+    run_cmd_and_assert_output(
+        cmd()
+            .arg(json.as_os_str())
+            .arg("--check")
+            .arg("--warn-overlaps"),
+        true,
+        "is matched by multiple entries in 'paths'",
+    );
+}
+
+#[test]
+fn invoke_ignore_files() {
+    let json = crate_root_rel("test-files/json/test-ok-ignore-files.json");
+    run_cmd_and_assert_output(
+        cmd().arg(json.as_os_str()).arg("--check"),
+        true,
+        "Found 1 files",
+    );
+
+    let json = crate_root_rel("test-files/json/test-err-ignore-files.json");
+    run_cmd_and_assert(cmd().arg(json.as_os_str()).arg("--check"), false);
+}
@@ -0,0 +1,30 @@
+use std::path;
+
+use run_clang_format::{cli, StyleSource};
+
+fn crate_root_rel(path: &str) -> path::PathBuf {
+    path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+#[test]
+fn format_buffer() {
+    let data = cli::Data::load(crate_root_rel("test-files/json/test-ok-format.json")).unwrap();
+    let cmd = run_clang_format::get_command(&data).unwrap();
+
+    // the file does not need to exist, the configured style file is used without placing it
+    let file = crate_root_rel("test-files/c-demo/pkg_a/generated.c");
+    let style = run_clang_format::style_source(&data, &file).unwrap();
+    let root = crate_root_rel("test-files").canonicalize().unwrap();
+    assert_eq!(
+        StyleSource::Placed {
+            style_file: root.join("clang-format/named.clang-format"),
+            placed_as: root.join("c-demo/.clang-format"),
+        },
+        style
+    );
+
+    let formatted = cmd
+        .format_buffer(b"int main(void)   \n", &file, style)
+        .unwrap();
+    assert_eq!(b"int main(void)\n".to_vec(), formatted);
+}